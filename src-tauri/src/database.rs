@@ -0,0 +1,679 @@
+use crate::profiles::check_name;
+use crate::storage::data_file;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+const DATABASE_FILE: &str = "history.sqlite3";
+// Events indexed straight from the history log, with no run to go with them,
+// are kept under made-up sessions, a day each.
+const IMPORTED_MODE: &str = "imported";
+// A longer pause between two attempts is a break, not practice time.
+const MAX_PRACTICE_GAP_MS: u64 = 30_000;
+
+// Schema changes, oldest first. The database's `user_version` is how many of
+// these have been applied; add new steps to the end and never edit old ones.
+const MIGRATIONS: &[&str] = &[
+    // 1: sessions and their events
+    "CREATE TABLE sessions (
+        id INTEGER PRIMARY KEY,
+        profile TEXT NOT NULL,
+        started TEXT NOT NULL,
+        mode TEXT NOT NULL,
+        UNIQUE (profile, started)
+    );
+    CREATE TABLE events (
+        id INTEGER PRIMARY KEY,
+        session_id INTEGER NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+        char_id TEXT NOT NULL,
+        at TEXT NOT NULL,
+        hit INTEGER NOT NULL,
+        reaction_ms INTEGER
+    );
+    CREATE INDEX events_session ON events (session_id);
+    CREATE INDEX events_at ON events (at);",
+    // 2: per-character queries without scanning every event
+    "CREATE INDEX events_char ON events (char_id, session_id);",
+    // 3: which history log frames are in, by where they start in the log
+    "CREATE TABLE log_frames (
+        profile TEXT NOT NULL,
+        log_offset INTEGER NOT NULL,
+        PRIMARY KEY (profile, log_offset)
+    );",
+];
+
+// Opened on first use and shared by every command.
+static DATABASE: Mutex<Option<Connection>> = Mutex::new(None);
+
+fn migrate(conn: &mut Connection) -> rusqlite::Result<()> {
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", i + 1)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+fn with_db<T>(
+    app: &AppHandle,
+    f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>,
+) -> Result<T, String> {
+    let mut guard = DATABASE.lock().map_err(|e| e.to_string())?;
+    if guard.is_none() {
+        let mut conn = Connection::open(data_file(app, DATABASE_FILE)?).map_err(|e| e.to_string())?;
+        conn.pragma_update(None, "foreign_keys", true)
+            .map_err(|e| e.to_string())?;
+        migrate(&mut conn).map_err(|e| e.to_string())?;
+        *guard = Some(conn);
+    }
+    f(guard.as_mut().unwrap()).map_err(|e| e.to_string())
+}
+
+/// A run, identified by its profile and start time.
+#[derive(Debug, Deserialize)]
+pub struct SessionKey {
+    pub started: String,
+    pub mode: String,
+}
+
+/// A hit or miss, with the character as the frontend serialises it.
+#[derive(Debug, Deserialize)]
+pub struct EventRow {
+    pub char_id: String,
+    pub at: String,
+    pub hit: bool,
+    pub reaction_ms: Option<u64>,
+}
+
+/// A frame of the history log: where it starts, and its events.
+#[derive(Debug, Deserialize)]
+pub struct LogFrame {
+    pub offset: u64,
+    pub events: Vec<EventRow>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CharAggregate {
+    pub char_id: String,
+    pub hits: u64,
+    pub misses: u64,
+    pub avg_ms: Option<f64>,
+    pub min_ms: Option<u64>,
+    pub max_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DayAggregate {
+    /// "YYYY-MM-DD"
+    pub day: String,
+    pub hits: u64,
+    pub misses: u64,
+    pub avg_ms: Option<f64>,
+    /// Time between attempts, leaving out breaks
+    pub practice_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionAggregate {
+    pub started: String,
+    pub ended: String,
+    pub mode: String,
+    pub hits: u64,
+    pub misses: u64,
+    pub avg_ms: Option<f64>,
+}
+
+// Adds events to a session, creating it the first time it is seen.
+fn insert_events(
+    conn: &Connection,
+    profile: &str,
+    session: &SessionKey,
+    events: &[EventRow],
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO sessions (profile, started, mode) VALUES (?1, ?2, ?3)",
+        params![profile, session.started, session.mode],
+    )?;
+    let session_id: i64 = conn.query_row(
+        "SELECT id FROM sessions WHERE profile = ?1 AND started = ?2",
+        params![profile, session.started],
+        |row| row.get(0),
+    )?;
+    let mut insert = conn.prepare(
+        "INSERT INTO events (session_id, char_id, at, hit, reaction_ms)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for event in events {
+        insert.execute(params![
+            session_id,
+            event.char_id,
+            event.at,
+            event.hit,
+            event.reaction_ms
+        ])?;
+    }
+    Ok(())
+}
+
+// Marks a log frame as indexed, returning false if it already was.
+fn add_frame(conn: &Connection, profile: &str, offset: u64) -> rusqlite::Result<bool> {
+    let added = conn.execute(
+        "INSERT OR IGNORE INTO log_frames (profile, log_offset) VALUES (?1, ?2)",
+        params![profile, offset],
+    )?;
+    Ok(added > 0)
+}
+
+fn add_session(
+    conn: &mut Connection,
+    profile: &str,
+    session: &SessionKey,
+    offset: u64,
+    events: &[EventRow],
+) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    if add_frame(&tx, profile, offset)? {
+        insert_events(&tx, profile, session, events)?;
+    }
+    tx.commit()
+}
+
+fn query_frames(conn: &Connection, profile: &str) -> rusqlite::Result<Vec<u64>> {
+    let mut query =
+        conn.prepare("SELECT log_offset FROM log_frames WHERE profile = ?1 ORDER BY log_offset")?;
+    let rows = query.query_map(params![profile], |row| row.get(0))?;
+    rows.collect()
+}
+
+fn index(conn: &mut Connection, profile: &str, frames: Vec<LogFrame>) -> rusqlite::Result<usize> {
+    let tx = conn.transaction()?;
+    let mut events = Vec::new();
+    for frame in frames {
+        if add_frame(&tx, profile, frame.offset)? {
+            events.extend(frame.events);
+        }
+    }
+    events.sort_by(|a, b| a.at.cmp(&b.at));
+    for day in events.chunk_by(|a, b| a.at.get(..10) == b.at.get(..10)) {
+        let session = SessionKey {
+            started: day[0].at.clone(),
+            mode: IMPORTED_MODE.to_string(),
+        };
+        insert_events(&tx, profile, &session, day)?;
+    }
+    tx.commit()?;
+    Ok(events.len())
+}
+
+/// Adds a run's events, already appended to the history log at `offset`,
+/// creating the session the first time it is seen. A frame that is already
+/// in is skipped, so a batch can be sent again after an error.
+#[tauri::command]
+pub fn add_session_events(
+    app: AppHandle,
+    profile: String,
+    session: SessionKey,
+    offset: u64,
+    events: Vec<EventRow>,
+) -> Result<(), String> {
+    check_name(&profile)?;
+    with_db(&app, |conn| {
+        add_session(conn, &profile, &session, offset, &events)
+    })
+}
+
+/// Where each history log frame the database has starts.
+#[tauri::command]
+pub fn indexed_frames(app: AppHandle, profile: String) -> Result<Vec<u64>, String> {
+    check_name(&profile)?;
+    with_db(&app, |conn| query_frames(conn, &profile))
+}
+
+/// Adds log frames the database missed, returning how many events were new.
+#[tauri::command]
+pub fn index_frames(
+    app: AppHandle,
+    profile: String,
+    frames: Vec<LogFrame>,
+) -> Result<usize, String> {
+    check_name(&profile)?;
+    with_db(&app, |conn| index(conn, &profile, frames))
+}
+
+fn query_chars(
+    conn: &Connection,
+    profile: &str,
+    since: Option<&str>,
+) -> rusqlite::Result<Vec<CharAggregate>> {
+    let mut query = conn.prepare(
+        "SELECT e.char_id, SUM(e.hit), SUM(1 - e.hit),
+                AVG(e.reaction_ms), MIN(e.reaction_ms), MAX(e.reaction_ms)
+         FROM events e JOIN sessions s ON s.id = e.session_id
+         WHERE s.profile = ?1 AND (?2 IS NULL OR e.at >= ?2)
+         GROUP BY e.char_id
+         ORDER BY e.char_id",
+    )?;
+    let rows = query.query_map(params![profile, since], |row| {
+        Ok(CharAggregate {
+            char_id: row.get(0)?,
+            hits: row.get(1)?,
+            misses: row.get(2)?,
+            avg_ms: row.get(3)?,
+            min_ms: row.get(4)?,
+            max_ms: row.get(5)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Totals per character, optionally only from `since` (an ISO date or time).
+#[tauri::command]
+pub fn char_aggregates(
+    app: AppHandle,
+    profile: String,
+    since: Option<String>,
+) -> Result<Vec<CharAggregate>, String> {
+    check_name(&profile)?;
+    with_db(&app, |conn| query_chars(conn, &profile, since.as_deref()))
+}
+
+fn query_days(
+    conn: &Connection,
+    profile: &str,
+    since: Option<&str>,
+) -> rusqlite::Result<Vec<DayAggregate>> {
+    // Practice time is the gaps between a day's attempts, in whatever
+    // session, up to the longest that isn't a break.
+    let mut query = conn.prepare(
+        "WITH timed AS (
+            SELECT substr(e.at, 1, 10) AS day, e.hit, e.reaction_ms,
+                   ROUND((julianday(e.at) - julianday(LAG(e.at) OVER (
+                       PARTITION BY substr(e.at, 1, 10) ORDER BY e.at
+                   ))) * 86400000) AS gap
+            FROM events e JOIN sessions s ON s.id = e.session_id
+            WHERE s.profile = ?1 AND (?2 IS NULL OR e.at >= ?2)
+         )
+         SELECT day, SUM(hit), SUM(1 - hit), AVG(reaction_ms),
+                CAST(TOTAL(CASE WHEN gap BETWEEN 0 AND ?3 THEN gap END) AS INTEGER)
+         FROM timed
+         GROUP BY day
+         ORDER BY day DESC",
+    )?;
+    let rows = query.query_map(params![profile, since, MAX_PRACTICE_GAP_MS], |row| {
+        Ok(DayAggregate {
+            day: row.get(0)?,
+            hits: row.get(1)?,
+            misses: row.get(2)?,
+            avg_ms: row.get(3)?,
+            practice_ms: row.get(4)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Totals per calendar day, newest first.
+#[tauri::command]
+pub fn daily_aggregates(
+    app: AppHandle,
+    profile: String,
+    since: Option<String>,
+) -> Result<Vec<DayAggregate>, String> {
+    check_name(&profile)?;
+    with_db(&app, |conn| query_days(conn, &profile, since.as_deref()))
+}
+
+fn query_sessions(
+    conn: &Connection,
+    profile: &str,
+    limit: u32,
+) -> rusqlite::Result<Vec<SessionAggregate>> {
+    let mut query = conn.prepare(
+        "SELECT s.started, MAX(e.at), s.mode, SUM(e.hit), SUM(1 - e.hit), AVG(e.reaction_ms)
+         FROM sessions s JOIN events e ON e.session_id = s.id
+         WHERE s.profile = ?1
+         GROUP BY s.id
+         ORDER BY s.started DESC
+         LIMIT ?2",
+    )?;
+    let rows = query.query_map(params![profile, limit], |row| {
+        Ok(SessionAggregate {
+            started: row.get(0)?,
+            ended: row.get(1)?,
+            mode: row.get(2)?,
+            hits: row.get(3)?,
+            misses: row.get(4)?,
+            avg_ms: row.get(5)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// The most recent sessions with their totals, newest first.
+#[tauri::command]
+pub fn session_aggregates(
+    app: AppHandle,
+    profile: String,
+    limit: u32,
+) -> Result<Vec<SessionAggregate>, String> {
+    check_name(&profile)?;
+    with_db(&app, |conn| query_sessions(conn, &profile, limit))
+}
+
+/// Removes a profile's sessions (and with them their events) and its index
+/// into the history log.
+pub fn delete_profile_sessions(app: &AppHandle, profile: &str) -> Result<(), String> {
+    with_db(app, |conn| {
+        conn.execute("DELETE FROM sessions WHERE profile = ?1", params![profile])?;
+        conn.execute(
+            "DELETE FROM log_frames WHERE profile = ?1",
+            params![profile],
+        )
+        .map(|_| ())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "foreign_keys", true).unwrap();
+        migrate(&mut conn).unwrap();
+        conn
+    }
+
+    fn user_version(conn: &Connection) -> usize {
+        conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    fn hit(char_id: &str, at: &str, reaction_ms: u64) -> EventRow {
+        EventRow {
+            char_id: char_id.to_string(),
+            at: at.to_string(),
+            hit: true,
+            reaction_ms: Some(reaction_ms),
+        }
+    }
+
+    fn miss(char_id: &str, at: &str) -> EventRow {
+        EventRow {
+            char_id: char_id.to_string(),
+            at: at.to_string(),
+            hit: false,
+            reaction_ms: None,
+        }
+    }
+
+    fn add(conn: &Connection, profile: &str, started: &str, events: &[EventRow]) {
+        let session = SessionKey {
+            started: started.to_string(),
+            mode: "a_z-qwerty".to_string(),
+        };
+        insert_events(conn, profile, &session, events).unwrap();
+    }
+
+    fn event_count(conn: &Connection) -> usize {
+        conn.query_row("SELECT COUNT(*) FROM events", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn new_database_gets_every_migration() {
+        let conn = open();
+        assert_eq!(user_version(&conn), MIGRATIONS.len());
+        let indexes: usize = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND name = 'events_char'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexes, 1);
+    }
+
+    #[test]
+    fn migrations_carry_on_from_user_version() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+        add(
+            &conn,
+            "ada",
+            "2025-03-01T10:00:00",
+            &[hit("a", "2025-03-01T10:00:01", 200)],
+        );
+
+        migrate(&mut conn).unwrap();
+        assert_eq!(user_version(&conn), MIGRATIONS.len());
+        assert_eq!(event_count(&conn), 1);
+        // Nothing left to do the second time.
+        migrate(&mut conn).unwrap();
+        assert_eq!(user_version(&conn), MIGRATIONS.len());
+    }
+
+    #[test]
+    fn events_join_an_existing_session() {
+        let conn = open();
+        add(
+            &conn,
+            "ada",
+            "2025-03-01T10:00:00",
+            &[hit("a", "2025-03-01T10:00:01", 200)],
+        );
+        add(
+            &conn,
+            "ada",
+            "2025-03-01T10:00:00",
+            &[miss("b", "2025-03-01T10:00:02")],
+        );
+        let sessions = query_sessions(&conn, "ada", 10).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!((sessions[0].hits, sessions[0].misses), (1, 1));
+    }
+
+    #[test]
+    fn char_aggregates_total_each_character() {
+        let conn = open();
+        add(
+            &conn,
+            "ada",
+            "2025-03-01T10:00:00",
+            &[
+                hit("a", "2025-03-01T10:00:01", 100),
+                hit("a", "2025-03-01T10:00:02", 300),
+                miss("a", "2025-03-01T10:00:03"),
+                hit("b", "2025-03-01T10:00:04", 250),
+            ],
+        );
+        add(
+            &conn,
+            "ada",
+            "2025-03-02T10:00:00",
+            &[miss("b", "2025-03-02T10:00:01")],
+        );
+        add(
+            &conn,
+            "bob",
+            "2025-03-01T10:00:00",
+            &[hit("a", "2025-03-01T10:00:01", 900)],
+        );
+
+        let chars = query_chars(&conn, "ada", None).unwrap();
+        assert_eq!(chars.len(), 2);
+        let a = &chars[0];
+        assert_eq!(a.char_id, "a");
+        assert_eq!((a.hits, a.misses), (2, 1));
+        assert_eq!(a.avg_ms, Some(200.0));
+        assert_eq!((a.min_ms, a.max_ms), (Some(100), Some(300)));
+        assert_eq!((chars[1].hits, chars[1].misses), (1, 1));
+
+        let since = query_chars(&conn, "ada", Some("2025-03-02")).unwrap();
+        assert_eq!(since.len(), 1);
+        assert_eq!((since[0].char_id.as_str(), since[0].misses), ("b", 1));
+        assert!(query_chars(&conn, "cy", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn daily_aggregates_leave_breaks_out_of_practice_time() {
+        let conn = open();
+        add(
+            &conn,
+            "ada",
+            "2025-03-01T10:00:00",
+            &[
+                hit("a", "2025-03-01T10:00:00", 100),
+                miss("b", "2025-03-01T10:00:01.500"),
+                hit("b", "2025-03-01T10:00:02", 300),
+                // A five minute break.
+                hit("c", "2025-03-01T10:05:02", 200),
+            ],
+        );
+        // Another session the same day follows on from the first.
+        add(
+            &conn,
+            "ada",
+            "2025-03-01T10:05:03",
+            &[hit("d", "2025-03-01T10:05:03.250", 400)],
+        );
+        add(
+            &conn,
+            "ada",
+            "2025-03-03T09:00:00",
+            &[miss("e", "2025-03-03T09:00:00")],
+        );
+
+        let days = query_days(&conn, "ada", None).unwrap();
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].day, "2025-03-03");
+        assert_eq!(
+            (days[0].hits, days[0].misses, days[0].practice_ms),
+            (0, 1, 0)
+        );
+        assert_eq!(days[0].avg_ms, None);
+        let first = &days[1];
+        assert_eq!(first.day, "2025-03-01");
+        assert_eq!((first.hits, first.misses), (4, 1));
+        assert_eq!(first.avg_ms, Some(250.0));
+        assert_eq!(first.practice_ms, 1_500 + 500 + 1_250);
+
+        let since = query_days(&conn, "ada", Some("2025-03-02")).unwrap();
+        assert_eq!(since.len(), 1);
+    }
+
+    #[test]
+    fn session_aggregates_are_newest_first() {
+        let conn = open();
+        add(
+            &conn,
+            "ada",
+            "2025-03-01T10:00:00",
+            &[hit("a", "2025-03-01T10:00:01", 100)],
+        );
+        add(
+            &conn,
+            "ada",
+            "2025-03-02T10:00:00",
+            &[
+                hit("a", "2025-03-02T10:00:01", 100),
+                miss("a", "2025-03-02T10:00:09"),
+            ],
+        );
+        add(
+            &conn,
+            "ada",
+            "2025-03-03T10:00:00",
+            &[hit("a", "2025-03-03T10:00:01", 100)],
+        );
+
+        let sessions = query_sessions(&conn, "ada", 2).unwrap();
+        let started: Vec<&str> = sessions.iter().map(|s| s.started.as_str()).collect();
+        assert_eq!(started, ["2025-03-03T10:00:00", "2025-03-02T10:00:00"]);
+        assert_eq!(sessions[1].ended, "2025-03-02T10:00:09");
+        assert_eq!(sessions[1].mode, "a_z-qwerty");
+        assert_eq!((sessions[1].hits, sessions[1].misses), (1, 1));
+    }
+
+    #[test]
+    fn a_batch_sent_again_is_added_once() {
+        let mut conn = open();
+        let session = SessionKey {
+            started: "2025-03-01T10:00:00".to_string(),
+            mode: "a_z-qwerty".to_string(),
+        };
+        let events = [hit("a", "2025-03-01T10:00:01", 100)];
+        for _ in 0..2 {
+            add_session(&mut conn, "ada", &session, 5, &events).unwrap();
+        }
+        // The same place in another profile's log is another frame.
+        add_session(&mut conn, "bob", &session, 5, &events).unwrap();
+        assert_eq!(event_count(&conn), 2);
+        assert_eq!(query_frames(&conn, "ada").unwrap(), [5]);
+    }
+
+    #[test]
+    fn index_only_adds_frames_it_is_missing() {
+        let mut conn = open();
+        let session = SessionKey {
+            started: "2025-03-02T10:00:00".to_string(),
+            mode: "a_z-qwerty".to_string(),
+        };
+        let sent = || vec![hit("a", "2025-03-02T10:00:01", 100)];
+        add_session(&mut conn, "ada", &session, 5, &sent()).unwrap();
+        let log = || {
+            vec![
+                LogFrame {
+                    offset: 5,
+                    events: sent(),
+                },
+                LogFrame {
+                    offset: 12,
+                    events: vec![
+                        hit("b", "2025-03-01T09:00:00", 100),
+                        miss("c", "2025-02-28T23:59:59"),
+                        hit("c", "2025-03-01T09:00:01", 100),
+                    ],
+                },
+            ]
+        };
+
+        assert_eq!(index(&mut conn, "ada", log()).unwrap(), 3);
+        assert_eq!(event_count(&conn), 4);
+        assert_eq!(query_frames(&conn, "ada").unwrap(), [5, 12]);
+        let sessions = query_sessions(&conn, "ada", 10).unwrap();
+        let imported: Vec<&str> = sessions
+            .iter()
+            .filter(|s| s.mode == IMPORTED_MODE)
+            .map(|s| s.started.as_str())
+            .collect();
+        assert_eq!(imported, ["2025-03-01T09:00:00", "2025-02-28T23:59:59"]);
+
+        // A second pass finds nothing new.
+        assert_eq!(index(&mut conn, "ada", log()).unwrap(), 0);
+        assert_eq!(event_count(&conn), 4);
+    }
+
+    #[test]
+    fn deleting_a_session_takes_its_events() {
+        let conn = open();
+        add(
+            &conn,
+            "ada",
+            "2025-03-01T10:00:00",
+            &[hit("a", "2025-03-01T10:00:01", 100)],
+        );
+        add(
+            &conn,
+            "bob",
+            "2025-03-01T10:00:00",
+            &[hit("a", "2025-03-01T10:00:01", 100)],
+        );
+        conn.execute("DELETE FROM sessions WHERE profile = ?1", params!["ada"])
+            .unwrap();
+        assert_eq!(event_count(&conn), 1);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::profiles::check_name;
+use crate::storage::data_dir;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use tauri::ipc::Response;
+use tauri::AppHandle;
+
+const HISTORY_DIR: &str = "history";
+
+// Each profile's practice history is kept in an append-only log, with the
+// history database indexing it. The log format belongs to the frontend;
+// this side only stores bytes.
+fn history_path(app: &AppHandle, profile: &str) -> Result<PathBuf, String> {
+    check_name(profile)?;
+    Ok(data_dir(app, HISTORY_DIR)?.join(format!("{}.log", profile)))
+}
+
+/// The profile's whole history log, sent as raw bytes rather than JSON.
+#[tauri::command]
+pub fn load_history(app: AppHandle, profile: String) -> Result<Response, String> {
+    let path = history_path(&app, &profile)?;
+    let bytes = if path.exists() {
+        fs::read(path).map_err(|e| e.to_string())?
+    } else {
+        Vec::new()
+    };
+    Ok(Response::new(bytes))
+}
+
+/// Appends a frame to the log in a single write and waits for it to reach
+/// the disk, so a crash can at worst leave one partial frame at the end. A
+/// new log gets `header` first; one that starts any other way (say, from a
+/// newer version) is left alone. Returns where the frame starts.
+#[tauri::command]
+pub fn append_history(
+    app: AppHandle,
+    profile: String,
+    header: Vec<u8>,
+    frame: Vec<u8>,
+) -> Result<u64, String> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(history_path(&app, &profile)?)
+        .map_err(|e| e.to_string())?;
+    let len = file.metadata().map_err(|e| e.to_string())?.len();
+    let mut bytes = Vec::with_capacity(header.len() + frame.len());
+    if len == 0 {
+        bytes.extend_from_slice(&header);
+    } else if !starts_with(&mut file, &header)? {
+        return Err("the history log is from another version of the app".to_string());
+    }
+    bytes.extend_from_slice(&frame);
+    file.write_all(&bytes).map_err(|e| e.to_string())?;
+    file.sync_data().map_err(|e| e.to_string())?;
+    Ok(len + (bytes.len() - frame.len()) as u64)
+}
+
+fn starts_with(file: &mut File, header: &[u8]) -> Result<bool, String> {
+    let mut start = vec![0; header.len()];
+    match file.read_exact(&mut start) {
+        Ok(()) => Ok(start == header),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Cuts a damaged tail off the log so new frames follow the last good one.
+#[tauri::command]
+pub fn truncate_history(app: AppHandle, profile: String, len: u64) -> Result<(), String> {
+    let path = history_path(&app, &profile)?;
+    if !path.exists() {
+        return Ok(());
+    }
+    let file = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+    if file.metadata().map_err(|e| e.to_string())?.len() > len {
+        file.set_len(len).map_err(|e| e.to_string())?;
+        file.sync_data().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Removes the profile's log, along with the profile.
+pub fn delete_history(app: &AppHandle, profile: &str) -> Result<(), String> {
+    let path = history_path(app, profile)?;
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use crate::storage::{data_file, load_json, save_json};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+const LEADERBOARD_FILE: &str = "leaderboard.json";
+
+// Serialised with the frontend's `LeaderboardEntry`; the backend only needs the
+// profile and mode to key on, so the bests themselves are passed through as-is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub profile: String,
+    pub mode: String,
+    pub best: serde_json::Value,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+// Several windows or profiles may submit at once; serialise the read-modify-write.
+static LEADERBOARD_LOCK: Mutex<()> = Mutex::new(());
+
+#[tauri::command]
+pub fn load_leaderboard(app: AppHandle) -> Result<Leaderboard, String> {
+    let _guard = LEADERBOARD_LOCK.lock().map_err(|e| e.to_string())?;
+    load_json(&data_file(&app, LEADERBOARD_FILE)?)
+}
+
+#[tauri::command]
+pub fn submit_leaderboard_entry(app: AppHandle, entry: LeaderboardEntry) -> Result<(), String> {
+    let _guard = LEADERBOARD_LOCK.lock().map_err(|e| e.to_string())?;
+    let path = data_file(&app, LEADERBOARD_FILE)?;
+    let mut leaderboard: Leaderboard = load_json(&path)?;
+
+    match leaderboard
+        .entries
+        .iter_mut()
+        .find(|e| e.profile == entry.profile && e.mode == entry.mode)
+    {
+        Some(existing) => *existing = entry,
+        None => leaderboard.entries.push(entry),
+    }
+
+    save_json(&path, &leaderboard)
+}
+
+/// Removes a deleted profile's entries.
+pub fn delete_leaderboard_entries(app: &AppHandle, profile: &str) -> Result<(), String> {
+    let _guard = LEADERBOARD_LOCK.lock().map_err(|e| e.to_string())?;
+    let path = data_file(app, LEADERBOARD_FILE)?;
+    let mut leaderboard: Leaderboard = load_json(&path)?;
+    let count = leaderboard.entries.len();
+    leaderboard.entries.retain(|e| e.profile != profile);
+    if leaderboard.entries.len() == count {
+        return Ok(());
+    }
+    save_json(&path, &leaderboard)
+}
@@ -0,0 +1,66 @@
+use crate::practice_text::{read_source, PracticeText};
+use crate::storage::data_dir;
+use serde::Serialize;
+use std::fs;
+use std::path::{Component, Path};
+use tauri::AppHandle;
+
+const LESSONS_DIR: &str = "lessons";
+const LESSON_EXTENSION: &str = "toml";
+// Lesson files are short; anything larger is a mistake.
+const MAX_LESSON_BYTES: u64 = 64 * 1024;
+
+#[derive(Debug, Serialize)]
+pub struct UserLesson {
+    pub name: String,
+    pub text: String,
+}
+
+/// Reads the user's own lesson files from `<app data>/lessons`. They are
+/// parsed and validated by the frontend, which reports errors per file.
+#[tauri::command]
+pub fn list_user_lessons(app: AppHandle) -> Result<Vec<UserLesson>, String> {
+    let dir = data_dir(&app, LESSONS_DIR)?;
+    let mut lessons = Vec::new();
+
+    for entry in fs::read_dir(dir).map_err(|e| e.to_string())?.flatten() {
+        let path = entry.path();
+        if path.extension().map_or(true, |e| !e.eq_ignore_ascii_case(LESSON_EXTENSION)) {
+            continue;
+        }
+        if entry.metadata().map_or(true, |m| m.len() > MAX_LESSON_BYTES) {
+            continue;
+        }
+        let Ok(text) = fs::read_to_string(&path) else {
+            continue;
+        };
+        lessons.push(UserLesson {
+            name: path
+                .file_name()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            text,
+        });
+    }
+
+    lessons.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(lessons)
+}
+
+/// Reads a lesson's `text_source`, a file or folder inside `<app data>/lessons`.
+#[tauri::command]
+pub fn load_lesson_text(app: AppHandle, path: String) -> Result<PracticeText, String> {
+    let outside = || format!("{} is not inside the lessons folder", path);
+    let relative = Path::new(&path);
+    if !relative.components().all(|part| matches!(part, Component::Normal(_))) {
+        return Err(outside());
+    }
+    let dir = data_dir(&app, LESSONS_DIR)?;
+    // A symlink could still lead out of the folder.
+    let source = dir.join(relative).canonicalize().map_err(|e| e.to_string())?;
+    if !source.starts_with(dir.canonicalize().map_err(|e| e.to_string())?) {
+        return Err(outside());
+    }
+    let text = read_source(&source)?;
+    Ok(PracticeText { path, text })
+}
@@ -1,3 +1,15 @@
+mod database;
+mod history;
+mod leaderboard;
+mod lessons;
+mod practice_text;
+mod profiles;
+mod race;
+mod recordings;
+mod reminders;
+mod snippets;
+mod storage;
+
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -8,7 +20,40 @@ fn greet(name: &str) -> String {
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![greet])
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            database::add_session_events,
+            database::char_aggregates,
+            database::daily_aggregates,
+            database::index_frames,
+            database::indexed_frames,
+            database::session_aggregates,
+            history::load_history,
+            history::append_history,
+            history::truncate_history,
+            leaderboard::load_leaderboard,
+            leaderboard::submit_leaderboard_entry,
+            lessons::list_user_lessons,
+            lessons::load_lesson_text,
+            practice_text::pick_practice_source,
+            practice_text::load_practice_text,
+            profiles::list_profiles,
+            profiles::load_profile,
+            profiles::save_profile,
+            profiles::save_settings,
+            profiles::delete_profile,
+            profiles::get_active_profile,
+            profiles::set_active_profile,
+            race::start_race_server,
+            race::stop_race_server,
+            recordings::list_recordings,
+            recordings::load_recording,
+            recordings::save_recording,
+            reminders::show_reminder,
+            snippets::list_user_snippets
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
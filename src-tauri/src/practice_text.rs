@@ -0,0 +1,132 @@
+use crate::storage::{data_file, load_json, save_json};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
+
+// Every file or folder picked in the dialog. Only these are read, so the
+// frontend can't ask for any other file; kept on disk because the chosen
+// source is remembered in settings between runs.
+const PICKED_SOURCES_FILE: &str = "practice_sources.json";
+
+const TEXT_EXTENSIONS: [&str; 14] = [
+    "txt", "md", "markdown", "rs", "py", "js", "ts", "sh", "toml", "json", "c", "h", "go", "java",
+];
+// Keep a careless pick of a huge folder from freezing the app.
+const MAX_FILE_BYTES: u64 = 256 * 1024;
+const MAX_TOTAL_BYTES: usize = 2 * 1024 * 1024;
+const MAX_DEPTH: usize = 4;
+
+#[derive(Debug, Serialize)]
+pub struct PracticeText {
+    pub path: String,
+    pub text: String,
+}
+
+fn is_text_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| TEXT_EXTENSIONS.contains(&ext.as_str()))
+}
+
+fn collect_files(dir: &Path, depth: usize, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            if depth < MAX_DEPTH {
+                collect_files(&path, depth + 1, files);
+            }
+        } else if is_text_file(&path) {
+            files.push(path);
+        }
+    }
+}
+
+fn read_text_file(path: &Path) -> Option<String> {
+    if fs::metadata(path).map_or(true, |m| m.len() > MAX_FILE_BYTES) {
+        return None;
+    }
+    // Binary or non UTF-8 files are skipped.
+    fs::read_to_string(path).ok()
+}
+
+fn picked_sources(app: &AppHandle) -> Result<Vec<String>, String> {
+    load_json(&data_file(app, PICKED_SOURCES_FILE)?)
+}
+
+fn remember_source(app: &AppHandle, path: &str) -> Result<(), String> {
+    let mut sources = picked_sources(app)?;
+    if !sources.iter().any(|source| source == path) {
+        sources.push(path.to_string());
+        save_json(&data_file(app, PICKED_SOURCES_FILE)?, &sources)?;
+    }
+    Ok(())
+}
+
+/// Reads a text file, or every text file under a folder, as one string.
+pub fn read_source(source: &Path) -> Result<String, String> {
+    if source.is_dir() {
+        let mut files = Vec::new();
+        collect_files(source, 0, &mut files);
+        files.sort();
+
+        let mut text = String::new();
+        for file in files {
+            if text.len() >= MAX_TOTAL_BYTES {
+                break;
+            }
+            if let Some(contents) = read_text_file(&file) {
+                text.push_str(&contents);
+                text.push_str("\n\n");
+            }
+        }
+        Ok(text)
+    } else if is_text_file(source) {
+        read_text_file(source).ok_or_else(|| format!("could not read {}", source.display()))
+    } else {
+        Err(format!("{} is not a text file", source.display()))
+    }
+}
+
+/// Opens a native picker for a text file or, with `directory`, a folder of files.
+#[tauri::command]
+pub async fn pick_practice_source(app: AppHandle, directory: bool) -> Result<Option<String>, String> {
+    let dialog = app.dialog().file();
+    let picked = if directory {
+        dialog.blocking_pick_folder()
+    } else {
+        dialog
+            .add_filter("Text", &TEXT_EXTENSIONS)
+            .blocking_pick_file()
+    };
+    let Some(path) = picked else {
+        return Ok(None);
+    };
+    let path = path.into_path().map_err(|e| e.to_string())?;
+    if !directory && !is_text_file(&path) {
+        return Err(format!("{} is not a text file", path.display()));
+    }
+    let path = path.to_string_lossy().into_owned();
+    remember_source(&app, &path)?;
+    Ok(Some(path))
+}
+
+/// Reads a practice source chosen earlier with `pick_practice_source`.
+#[tauri::command]
+pub fn load_practice_text(app: AppHandle, path: String) -> Result<PracticeText, String> {
+    if !picked_sources(&app)?.contains(&path) {
+        return Err(format!("{} was not picked as a practice source", path));
+    }
+    let text = read_source(Path::new(&path))?;
+    Ok(PracticeText { path, text })
+}
@@ -0,0 +1,123 @@
+use crate::database::delete_profile_sessions;
+use crate::history::delete_history;
+use crate::leaderboard::delete_leaderboard_entries;
+use crate::recordings::delete_recordings;
+use crate::storage::{data_dir, data_file, load_json, save_json};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+const PROFILES_DIR: &str = "profiles";
+const APP_STATE_FILE: &str = "app_state.json";
+const MAX_NAME_LEN: usize = 32;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AppState {
+    active_profile: Option<String>,
+}
+
+/// The rule for profile names, guarding every path built from one. The
+/// frontend's `Profile::is_valid_name` checks names the same way before
+/// sending them, but this is the check that counts.
+pub fn check_name(name: &str) -> Result<(), String> {
+    // Names come from the UI and end up as file names, so keep them boring.
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == ' ')
+        && name.trim() == name;
+    if !valid {
+        return Err(format!("invalid profile name: {:?}", name));
+    }
+    Ok(())
+}
+
+fn profile_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    check_name(name)?;
+    Ok(data_dir(app, PROFILES_DIR)?.join(format!("{}.json", name)))
+}
+
+#[tauri::command]
+pub fn list_profiles(app: AppHandle) -> Result<Vec<String>, String> {
+    let dir = data_dir(&app, PROFILES_DIR)?;
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .collect();
+    names.sort_unstable();
+    Ok(names)
+}
+
+/// Returns `None` when the profile has never been saved.
+#[tauri::command]
+pub fn load_profile(app: AppHandle, name: String) -> Result<Option<serde_json::Value>, String> {
+    let path = profile_path(&app, &name)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    load_json(&path)
+}
+
+#[tauri::command]
+pub fn save_profile(app: AppHandle, name: String, profile: serde_json::Value) -> Result<(), String> {
+    save_json(&profile_path(&app, &name)?, &profile)
+}
+
+/// Updates only the settings of a saved profile, leaving the rest untouched.
+#[tauri::command]
+pub fn save_settings(
+    app: AppHandle,
+    name: String,
+    settings: serde_json::Value,
+) -> Result<(), String> {
+    let path = profile_path(&app, &name)?;
+    let mut profile: serde_json::Value = load_json(&path)?;
+    if !profile.is_object() {
+        profile = serde_json::json!({ "name": name });
+    }
+    profile["settings"] = settings;
+    save_json(&path, &profile)
+}
+
+/// Deletes a profile and everything kept for it, forgetting it as the
+/// active one if it was.
+#[tauri::command]
+pub fn delete_profile(app: AppHandle, name: String) -> Result<(), String> {
+    let path = profile_path(&app, &name)?;
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| e.to_string())?;
+    }
+    delete_history(&app, &name)?;
+    delete_recordings(&app, &name)?;
+    delete_profile_sessions(&app, &name)?;
+    delete_leaderboard_entries(&app, &name)?;
+
+    let state_path = data_file(&app, APP_STATE_FILE)?;
+    let mut state: AppState = load_json(&state_path)?;
+    if state.active_profile.as_deref() == Some(name.as_str()) {
+        state.active_profile = None;
+        save_json(&state_path, &state)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_active_profile(app: AppHandle) -> Result<Option<String>, String> {
+    let state: AppState = load_json(&data_file(&app, APP_STATE_FILE)?)?;
+    Ok(state.active_profile)
+}
+
+#[tauri::command]
+pub fn set_active_profile(app: AppHandle, name: String) -> Result<(), String> {
+    // Validate before remembering it.
+    profile_path(&app, &name)?;
+    let path = data_file(&app, APP_STATE_FILE)?;
+    let mut state: AppState = load_json(&path)?;
+    state.active_profile = Some(name);
+    save_json(&path, &state)
+}
@@ -0,0 +1,517 @@
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::UdpSocket;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::async_runtime;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, watch};
+use tokio_tungstenite::tungstenite::Message;
+
+pub const DEFAULT_PORT: u16 = 7878;
+// Time between a race being started and typing opening, in milliseconds.
+const COUNTDOWN_MS: u64 = 3000;
+// Racers still typing when time runs out don't finish, so nobody idle can
+// hold a race up: time runs out after a second a character, or this long
+// after the first racer finishes if that comes sooner.
+const MS_PER_CHAR_LIMIT: u64 = 1000;
+const FINISH_GRACE_MS: u64 = 30_000;
+// How often the server checks whether time has run out.
+const TICK_MS: u64 = 500;
+const MAX_NAME_LEN: usize = 32;
+const MAX_SEQUENCE_LEN: usize = 1000;
+
+// The protocol is JSON text frames, tagged by "type"; the frontend's
+// `race.rs` has the other half.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Join { name: String },
+    /// Races everyone connected on `sequence`
+    Start { sequence: String },
+    /// Characters typed so far, and misses on the way
+    Progress { position: usize, misses: usize },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage {
+    /// Sent to a client alone, with its player id
+    Welcome { id: u32 },
+    /// Everyone connected, and the length of the race under way or last run
+    Players { players: Vec<Player>, length: usize },
+    Start { sequence: String, countdown_ms: u64 },
+    /// Every racer has finished or run out of time; best first, with
+    /// those who didn't finish last
+    Results { standings: Vec<Player> },
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Player {
+    id: u32,
+    name: String,
+    /// Whether they're in the race under way, rather than watching
+    racing: bool,
+    position: usize,
+    misses: usize,
+    /// Milliseconds from the start to typing the last character
+    finished_ms: Option<u64>,
+}
+
+impl Player {
+    fn accuracy(&self) -> f64 {
+        match self.position + self.misses {
+            0 => 0.0,
+            total => self.position as f64 / total as f64,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Race {
+    next_id: u32,
+    players: BTreeMap<u32, Player>,
+    length: usize,
+    /// When typing opened, while a race is under way
+    started: Option<Instant>,
+    /// When the race under way ends for those still typing
+    deadline: Option<Instant>,
+}
+
+impl Race {
+    fn players(&self) -> ServerMessage {
+        ServerMessage::Players {
+            players: self.players.values().cloned().collect(),
+            length: self.length,
+        }
+    }
+
+    fn add_player(&mut self) -> u32 {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.players.insert(
+            id,
+            Player {
+                id,
+                name: format!("Player {}", id),
+                racing: false,
+                position: 0,
+                misses: 0,
+                finished_ms: None,
+            },
+        );
+        id
+    }
+
+    fn remove_player(&mut self, id: u32) -> Vec<ServerMessage> {
+        self.players.remove(&id);
+        let mut out = vec![self.players()];
+        out.extend(self.check_finished());
+        out
+    }
+
+    /// What to broadcast in answer to a client's message.
+    fn handle(&mut self, id: u32, message: ClientMessage) -> Vec<ServerMessage> {
+        match message {
+            ClientMessage::Join { name } => {
+                let name: String = name.trim().chars().take(MAX_NAME_LEN).collect();
+                if let (false, Some(player)) = (name.is_empty(), self.players.get_mut(&id)) {
+                    player.name = name;
+                }
+                vec![self.players()]
+            }
+            ClientMessage::Start { sequence } => {
+                let length = sequence.chars().count();
+                if self.started.is_some() || length == 0 || length > MAX_SEQUENCE_LEN {
+                    return Vec::new();
+                }
+                self.length = length;
+                let started = Instant::now() + Duration::from_millis(COUNTDOWN_MS);
+                self.started = Some(started);
+                let limit = Duration::from_millis(length as u64 * MS_PER_CHAR_LIMIT);
+                self.deadline = Some(started + limit);
+                for player in self.players.values_mut() {
+                    player.racing = true;
+                    player.position = 0;
+                    player.misses = 0;
+                    player.finished_ms = None;
+                }
+                vec![
+                    ServerMessage::Start {
+                        sequence,
+                        countdown_ms: COUNTDOWN_MS,
+                    },
+                    self.players(),
+                ]
+            }
+            ClientMessage::Progress { position, misses } => {
+                // Nothing counts during the countdown.
+                let Some(started) = self.started.filter(|&started| Instant::now() >= started)
+                else {
+                    return Vec::new();
+                };
+                let length = self.length;
+                let Some(player) = self.players.get_mut(&id).filter(|p| p.racing) else {
+                    return Vec::new();
+                };
+                // Hits arrive one at a time, and misses can't be taken back.
+                if player.finished_ms.is_some() || position > player.position + 1 {
+                    return Vec::new();
+                }
+                player.position = position.max(player.position).min(length);
+                player.misses = misses.max(player.misses);
+                if player.position == length {
+                    player.finished_ms = Some(started.elapsed().as_millis() as u64);
+                    let grace = Instant::now() + Duration::from_millis(FINISH_GRACE_MS);
+                    self.deadline = self.deadline.map(|deadline| deadline.min(grace));
+                }
+                let mut out = vec![self.players()];
+                out.extend(self.check_finished());
+                out
+            }
+        }
+    }
+
+    // Ends the race once everyone in it has finished (or left).
+    fn check_finished(&mut self) -> Option<ServerMessage> {
+        self.started?;
+        if self.players.values().any(|p| p.racing && p.finished_ms.is_none()) {
+            return None;
+        }
+        Some(self.end_race())
+    }
+
+    /// Ends the race under way if its time has run out by `now`.
+    fn tick(&mut self, now: Instant) -> Option<ServerMessage> {
+        self.deadline.filter(|&deadline| self.started.is_some() && now >= deadline)?;
+        Some(self.end_race())
+    }
+
+    fn end_race(&mut self) -> ServerMessage {
+        self.started = None;
+        self.deadline = None;
+        let mut racers: Vec<Player> = self.players.values().filter(|p| p.racing).cloned().collect();
+        // Fastest first; accuracy settles a tie. Those who didn't finish
+        // come last, furthest along first.
+        racers.sort_by(|a, b| {
+            a.finished_ms
+                .is_none()
+                .cmp(&b.finished_ms.is_none())
+                .then(a.finished_ms.cmp(&b.finished_ms))
+                .then(b.position.cmp(&a.position))
+                .then(b.accuracy().total_cmp(&a.accuracy()))
+        });
+        ServerMessage::Results { standings: racers }
+    }
+}
+
+/// Where clients can reach the server.
+#[derive(Debug, Clone, Serialize)]
+pub struct RaceServer {
+    pub port: u16,
+    /// WebSocket URLs, on this machine first and then on the LAN
+    pub urls: Vec<String>,
+}
+
+struct Running {
+    info: RaceServer,
+    shutdown: watch::Sender<bool>,
+}
+
+static SERVER: Mutex<Option<Running>> = Mutex::new(None);
+
+// The address other machines reach this one on. Connecting a UDP socket
+// sends nothing; it only picks the interface with the default route.
+fn lan_address() -> Option<String> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:80").ok()?;
+    Some(socket.local_addr().ok()?.ip().to_string())
+}
+
+async fn send(sink: &mut (impl SinkExt<Message> + Unpin), message: &ServerMessage) -> bool {
+    match serde_json::to_string(message) {
+        Ok(text) => sink.send(Message::text(text)).await.is_ok(),
+        Err(_) => true,
+    }
+}
+
+async fn serve_client(
+    stream: TcpStream,
+    race: Arc<Mutex<Race>>,
+    broadcaster: broadcast::Sender<ServerMessage>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let Ok(socket) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    let (mut sink, mut source) = socket.split();
+    let mut updates = broadcaster.subscribe();
+    let id = race.lock().unwrap().add_player();
+    if !send(&mut sink, &ServerMessage::Welcome { id }).await {
+        race.lock().unwrap().remove_player(id);
+        return;
+    }
+    let _ = broadcaster.send(race.lock().unwrap().players());
+
+    loop {
+        tokio::select! {
+            incoming = source.next() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    let Ok(message) = serde_json::from_str::<ClientMessage>(&text) else {
+                        continue;
+                    };
+                    for out in race.lock().unwrap().handle(id, message) {
+                        let _ = broadcaster.send(out);
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            update = updates.recv() => match update {
+                Ok(message) => {
+                    if !send(&mut sink, &message).await {
+                        break;
+                    }
+                }
+                // A slow client can skip updates; the next one has it all.
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = shutdown.changed() => break,
+        }
+    }
+
+    let out = race.lock().unwrap().remove_player(id);
+    for message in out {
+        let _ = broadcaster.send(message);
+    }
+}
+
+/// Starts hosting races on `port` (or the default), on every interface so
+/// the LAN can join. Hosting again while running just reports where.
+#[tauri::command]
+pub async fn start_race_server(port: Option<u16>) -> Result<RaceServer, String> {
+    if let Some(running) = SERVER.lock().map_err(|e| e.to_string())?.as_ref() {
+        return Ok(running.info.clone());
+    }
+    let listener = TcpListener::bind(("0.0.0.0", port.unwrap_or(DEFAULT_PORT)))
+        .await
+        .map_err(|e| e.to_string())?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let mut urls = vec![format!("ws://localhost:{}", port)];
+    urls.extend(lan_address().map(|address| format!("ws://{}:{}", address, port)));
+    let info = RaceServer { port, urls };
+
+    let (shutdown, mut stopped) = watch::channel(false);
+    let race = Arc::new(Mutex::new(Race::default()));
+    let (broadcaster, _) = broadcast::channel(64);
+    let clients = shutdown.subscribe();
+    async_runtime::spawn(async move {
+        let mut ticks = tokio::time::interval(Duration::from_millis(TICK_MS));
+        loop {
+            tokio::select! {
+                _ = ticks.tick() => {
+                    let out = race.lock().unwrap().tick(Instant::now());
+                    if let Some(results) = out {
+                        let _ = broadcaster.send(results);
+                    }
+                }
+                accepted = listener.accept() => {
+                    if let Ok((stream, _)) = accepted {
+                        async_runtime::spawn(serve_client(
+                            stream,
+                            race.clone(),
+                            broadcaster.clone(),
+                            clients.clone(),
+                        ));
+                    }
+                }
+                _ = stopped.changed() => break,
+            }
+        }
+    });
+
+    *SERVER.lock().map_err(|e| e.to_string())? = Some(Running {
+        info: info.clone(),
+        shutdown,
+    });
+    Ok(info)
+}
+
+/// Stops hosting and disconnects everyone.
+#[tauri::command]
+pub fn stop_race_server() -> Result<(), String> {
+    if let Some(running) = SERVER.lock().map_err(|e| e.to_string())?.take() {
+        let _ = running.shutdown.send(true);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start(race: &mut Race, sequence: &str) -> Vec<ServerMessage> {
+        race.handle(
+            1,
+            ClientMessage::Start {
+                sequence: sequence.to_string(),
+            },
+        )
+    }
+
+    // Skips the countdown, as if it had just run out.
+    fn open_typing(race: &mut Race) {
+        race.started = Some(Instant::now());
+    }
+
+    fn progress(race: &mut Race, id: u32, position: usize, misses: usize) -> Vec<ServerMessage> {
+        race.handle(id, ClientMessage::Progress { position, misses })
+    }
+
+    fn player(race: &Race, id: u32) -> &Player {
+        &race.players[&id]
+    }
+
+    fn standings(out: &[ServerMessage]) -> Option<Vec<u32>> {
+        out.iter().find_map(|message| match message {
+            ServerMessage::Results { standings } => Some(standings.iter().map(|p| p.id).collect()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn join_renames_the_player() {
+        let mut race = Race::default();
+        let id = race.add_player();
+        let join = |name: &str| ClientMessage::Join {
+            name: name.to_string(),
+        };
+        race.handle(id, join("  Ada  "));
+        assert_eq!(player(&race, id).name, "Ada");
+        race.handle(id, join(" "));
+        assert_eq!(player(&race, id).name, "Ada");
+    }
+
+    #[test]
+    fn start_puts_everyone_in_the_race() {
+        let mut race = Race::default();
+        let (a, b) = (race.add_player(), race.add_player());
+        let out = start(&mut race, "abc");
+        assert!(matches!(
+            out[0],
+            ServerMessage::Start {
+                countdown_ms: COUNTDOWN_MS,
+                ..
+            }
+        ));
+        assert_eq!(race.length, 3);
+        assert!(player(&race, a).racing && player(&race, b).racing);
+    }
+
+    #[test]
+    fn second_start_is_rejected_while_racing() {
+        let mut race = Race::default();
+        race.add_player();
+        start(&mut race, "abc");
+        assert!(start(&mut race, "abcdef").is_empty());
+        assert_eq!(race.length, 3);
+    }
+
+    #[test]
+    fn progress_is_ignored_during_the_countdown() {
+        let mut race = Race::default();
+        let id = race.add_player();
+        start(&mut race, "a");
+        assert!(progress(&mut race, id, 1, 0).is_empty());
+        assert_eq!(player(&race, id).position, 0);
+        assert_eq!(player(&race, id).finished_ms, None);
+    }
+
+    #[test]
+    fn progress_moves_one_hit_at_a_time() {
+        let mut race = Race::default();
+        let id = race.add_player();
+        start(&mut race, "abcd");
+        open_typing(&mut race);
+        progress(&mut race, id, 1, 0);
+        assert!(progress(&mut race, id, 4, 0).is_empty());
+        assert_eq!(player(&race, id).position, 1);
+        progress(&mut race, id, 2, 2);
+        progress(&mut race, id, 2, 1);
+        assert_eq!(player(&race, id).position, 2);
+        assert_eq!(player(&race, id).misses, 2);
+    }
+
+    #[test]
+    fn results_come_once_everyone_finishes() {
+        let mut race = Race::default();
+        let (a, b) = (race.add_player(), race.add_player());
+        start(&mut race, "ab");
+        open_typing(&mut race);
+        progress(&mut race, a, 1, 0);
+        assert_eq!(standings(&progress(&mut race, a, 2, 0)), None);
+        assert!(player(&race, a).finished_ms.is_some());
+        progress(&mut race, b, 1, 1);
+        let out = progress(&mut race, b, 2, 1);
+        assert_eq!(standings(&out), Some(vec![a, b]));
+        assert!(race.started.is_none());
+        // Nothing more counts once the race is over.
+        assert!(progress(&mut race, b, 2, 3).is_empty());
+    }
+
+    #[test]
+    fn leaving_mid_race_lets_the_rest_finish() {
+        let mut race = Race::default();
+        let (a, b) = (race.add_player(), race.add_player());
+        start(&mut race, "a");
+        open_typing(&mut race);
+        progress(&mut race, a, 1, 0);
+        let out = race.remove_player(b);
+        assert_eq!(standings(&out), Some(vec![a]));
+        assert!(race.started.is_none());
+    }
+
+    #[test]
+    fn time_runs_out_after_the_first_finisher() {
+        let mut race = Race::default();
+        let (a, b) = (race.add_player(), race.add_player());
+        start(&mut race, "ab");
+        open_typing(&mut race);
+        progress(&mut race, a, 1, 0);
+        progress(&mut race, a, 2, 0);
+        assert!(race.tick(Instant::now()).is_none());
+        let grace = Duration::from_millis(FINISH_GRACE_MS + 1);
+        let out = race.tick(Instant::now() + grace).into_iter().collect::<Vec<_>>();
+        assert_eq!(standings(&out), Some(vec![a, b]));
+        assert_eq!(player(&race, b).finished_ms, None);
+        assert!(race.started.is_none());
+        assert!(race.tick(Instant::now() + grace).is_none());
+    }
+
+    #[test]
+    fn time_runs_out_with_nobody_finished() {
+        let mut race = Race::default();
+        let (a, b) = (race.add_player(), race.add_player());
+        start(&mut race, "abc");
+        open_typing(&mut race);
+        progress(&mut race, b, 1, 0);
+        assert!(race.tick(Instant::now()).is_none());
+        // The countdown, then a second a character.
+        let limit = Duration::from_millis(COUNTDOWN_MS + 3 * MS_PER_CHAR_LIMIT);
+        let out = race.tick(Instant::now() + limit).into_iter().collect::<Vec<_>>();
+        assert_eq!(standings(&out), Some(vec![b, a]));
+    }
+
+    #[test]
+    fn late_joiners_watch() {
+        let mut race = Race::default();
+        let a = race.add_player();
+        start(&mut race, "a");
+        open_typing(&mut race);
+        let b = race.add_player();
+        assert!(progress(&mut race, b, 1, 0).is_empty());
+        assert_eq!(standings(&progress(&mut race, a, 1, 0)), Some(vec![a]));
+    }
+}
@@ -0,0 +1,99 @@
+use crate::profiles::check_name;
+use crate::storage::data_dir;
+use serde_json::Value;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+const RECORDINGS_DIR: &str = "recordings";
+// Recordings are JSON lines: a header with everything but the events, then
+// an event per line, so saving during a run only appends what's new.
+const RECORDING_EXTENSION: &str = "jsonl";
+
+fn recordings_dir(app: &AppHandle, profile: &str) -> Result<PathBuf, String> {
+    // One folder per profile, so deleting a profile's recordings is easy.
+    check_name(profile)?;
+    let dir = data_dir(app, RECORDINGS_DIR)?.join(profile);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// Removes the profile's folder of recordings, along with the profile.
+pub fn delete_recordings(app: &AppHandle, profile: &str) -> Result<(), String> {
+    check_name(profile)?;
+    let dir = data_dir(app, RECORDINGS_DIR)?.join(profile);
+    if dir.exists() {
+        fs::remove_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn recording_path(app: &AppHandle, profile: &str, id: &str) -> Result<PathBuf, String> {
+    // Ids are start times like "20250301-140509-123".
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit() || c == '-') {
+        return Err(format!("invalid recording id: {:?}", id));
+    }
+    Ok(recordings_dir(app, profile)?.join(format!("{}.{}", id, RECORDING_EXTENSION)))
+}
+
+/// Ids of the profile's saved recordings, newest first.
+#[tauri::command]
+pub fn list_recordings(app: AppHandle, profile: String) -> Result<Vec<String>, String> {
+    let dir = recordings_dir(&app, &profile)?;
+    let mut ids: Vec<String> = fs::read_dir(dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == RECORDING_EXTENSION))
+        .filter_map(|path| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .collect();
+    ids.sort_unstable_by(|a, b| b.cmp(a));
+    Ok(ids)
+}
+
+/// A saved recording, put back together as one object with its events.
+#[tauri::command]
+pub fn load_recording(
+    app: AppHandle,
+    profile: String,
+    id: String,
+) -> Result<Option<Value>, String> {
+    let path = recording_path(&app, &profile, &id)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let mut lines = text.lines();
+    let Some(Ok(Value::Object(mut recording))) = lines.next().map(serde_json::from_str) else {
+        return Err(format!("recording {} is damaged", id));
+    };
+    // A crash mid-save can leave the last line cut short.
+    let events = lines.map_while(|line| serde_json::from_str(line).ok()).collect();
+    recording.insert("events".to_string(), Value::Array(events));
+    Ok(Some(Value::Object(recording)))
+}
+
+/// Saves a recording a piece at a time: `header` starts the file afresh,
+/// and `events` are added after those already saved.
+#[tauri::command]
+pub fn save_recording(
+    app: AppHandle,
+    profile: String,
+    id: String,
+    header: Option<Value>,
+    events: Vec<Value>,
+) -> Result<(), String> {
+    let path = recording_path(&app, &profile, &id)?;
+    let mut file = match header {
+        Some(_) => File::create(&path),
+        None => OpenOptions::new().append(true).open(&path),
+    }
+    .map_err(|e| e.to_string())?;
+    let mut text = String::new();
+    for line in header.iter().chain(&events) {
+        text.push_str(&serde_json::to_string(line).map_err(|e| e.to_string())?);
+        text.push('\n');
+    }
+    file.write_all(text.as_bytes()).map_err(|e| e.to_string())
+}
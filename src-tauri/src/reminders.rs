@@ -0,0 +1,14 @@
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+/// Shows a desktop notification. The frontend knows the goal and the
+/// progress towards it, so it decides when a reminder is due.
+#[tauri::command]
+pub fn show_reminder(app: AppHandle, title: String, body: String) -> Result<(), String> {
+    app.notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .show()
+        .map_err(|e| e.to_string())
+}
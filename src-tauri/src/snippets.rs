@@ -0,0 +1,51 @@
+use crate::storage::data_dir;
+use serde::Serialize;
+use std::fs;
+use tauri::AppHandle;
+
+const SNIPPETS_DIR: &str = "snippets";
+const SNIPPET_EXTENSIONS: [&str; 7] = ["rs", "py", "js", "mjs", "ts", "sh", "bash"];
+// Anything larger is not a snippet.
+const MAX_SNIPPET_BYTES: u64 = 16 * 1024;
+
+#[derive(Debug, Serialize)]
+pub struct UserSnippet {
+    pub name: String,
+    pub extension: String,
+    pub code: String,
+}
+
+/// Reads the user's own snippets from `<app data>/snippets`.
+#[tauri::command]
+pub fn list_user_snippets(app: AppHandle) -> Result<Vec<UserSnippet>, String> {
+    let dir = data_dir(&app, SNIPPETS_DIR)?;
+    let mut snippets = Vec::new();
+
+    for entry in fs::read_dir(dir).map_err(|e| e.to_string())?.flatten() {
+        let path = entry.path();
+        let Some(extension) = path.extension().map(|e| e.to_string_lossy().to_lowercase()) else {
+            continue;
+        };
+        if !SNIPPET_EXTENSIONS.contains(&extension.as_str()) {
+            continue;
+        }
+        if entry.metadata().map_or(true, |m| m.len() > MAX_SNIPPET_BYTES) {
+            continue;
+        }
+        // Skip files that aren't valid UTF-8 rather than failing the whole list.
+        let Ok(code) = fs::read_to_string(&path) else {
+            continue;
+        };
+        snippets.push(UserSnippet {
+            name: path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            extension,
+            code,
+        });
+    }
+
+    snippets.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(snippets)
+}
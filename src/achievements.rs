@@ -0,0 +1,237 @@
+use crate::history::{day_streaks, History};
+use crate::layout::Layout;
+use crate::settings::CharSet;
+use crate::stats::Stats;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Thresholds for the achievements below.
+const STREAK_HITS: usize = 100;
+const FAST_SYMBOL_MS: u64 = 500;
+const STREAK_DAYS: usize = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Achievement {
+    /// 100 hits in a row
+    HundredInARow,
+    /// Every symbol typed at under 500 ms on average in one run
+    SymbolSprinter,
+    /// Practice on seven days running
+    WeekStreak,
+    /// Every key of the layout hit in one run without a single miss
+    CleanSweep,
+}
+
+/// What achievements are judged on.
+pub struct Context<'a> {
+    /// The run so far
+    pub stats: &'a Stats,
+    pub history: &'a History,
+    pub layout: Layout,
+    pub today: NaiveDate,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 4] = [
+        Achievement::HundredInARow,
+        Achievement::SymbolSprinter,
+        Achievement::WeekStreak,
+        Achievement::CleanSweep,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Achievement::HundredInARow => "hundred_in_a_row",
+            Achievement::SymbolSprinter => "symbol_sprinter",
+            Achievement::WeekStreak => "week_streak",
+            Achievement::CleanSweep => "clean_sweep",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Achievement::HundredInARow => "Hundred in a Row",
+            Achievement::SymbolSprinter => "Symbol Sprinter",
+            Achievement::WeekStreak => "Week Streak",
+            Achievement::CleanSweep => "Clean Sweep",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Achievement::HundredInARow => "Hit 100 characters in a row without a miss.",
+            Achievement::SymbolSprinter => {
+                "Type every symbol in under 500 ms on average in a single run."
+            }
+            Achievement::WeekStreak => "Practise on 7 days in a row.",
+            Achievement::CleanSweep => {
+                "Hit every key on the keyboard in a single run without a single miss."
+            }
+        }
+    }
+
+    pub fn is_earned(&self, context: &Context) -> bool {
+        let stats = context.stats;
+        match self {
+            Achievement::HundredInARow => stats.get_longest_streak() >= STREAK_HITS,
+            Achievement::SymbolSprinter => CharSet::Symbols
+                .pool(context.layout)
+                .iter()
+                .all(|symbol| {
+                    stats
+                        .get_char_stats(symbol)
+                        .is_some_and(|s| s.total_hits > 0 && s.hit_avg < FAST_SYMBOL_MS)
+                }),
+            Achievement::WeekStreak => {
+                let (current, _) = day_streaks(&context.history.practice_days(), context.today);
+                current >= STREAK_DAYS
+            }
+            Achievement::CleanSweep => {
+                stats.get_total_miss_count() == 0
+                    && CharSet::All.pool(context.layout).iter().all(|key| {
+                        stats
+                            .get_char_stats(key)
+                            .is_some_and(|s| s.total_hits > 0)
+                    })
+            }
+        }
+    }
+}
+
+/// Achievements a profile has unlocked, and when.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Achievements {
+    unlocked: HashMap<Achievement, NaiveDateTime>,
+}
+
+impl Achievements {
+    pub fn unlocked_at(&self, achievement: Achievement) -> Option<NaiveDateTime> {
+        self.unlocked.get(&achievement).copied()
+    }
+
+    /// Unlocks whatever has been earned since the last check and returns it.
+    pub fn evaluate(&mut self, context: &Context, now: NaiveDateTime) -> Vec<Achievement> {
+        let earned: Vec<Achievement> = Achievement::ALL
+            .into_iter()
+            .filter(|achievement| !self.unlocked.contains_key(achievement))
+            .filter(|achievement| achievement.is_earned(context))
+            .collect();
+        for achievement in &earned {
+            self.unlocked.insert(*achievement, now);
+        }
+        earned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::HistoryEvent;
+    use crate::stats::HitType;
+    use crate::target_char::TargetChar;
+    use crate::test_fixtures::{at, hit, miss, stats_of};
+
+    const DAY_MS: i64 = 86_400_000;
+
+    // A hit on each of `keys` in turn, each taking `reaction_time`.
+    fn hits(keys: &[TargetChar], reaction_time: u64) -> Vec<HistoryEvent> {
+        keys.iter()
+            .enumerate()
+            .map(|(i, &char_id)| HistoryEvent {
+                char_id,
+                result: HitType::Hit(at(i as i64 * 1_000), reaction_time),
+            })
+            .collect()
+    }
+
+    fn context<'a>(stats: &'a Stats, history: &'a History) -> Context<'a> {
+        Context {
+            stats,
+            history,
+            layout: Layout::Qwerty,
+            today: at(0).date(),
+        }
+    }
+
+    // Whether a run of `events` earns `achievement`, with no other history.
+    fn earned_by(achievement: Achievement, events: &[HistoryEvent]) -> bool {
+        achievement.is_earned(&context(&stats_of(events), &History::default()))
+    }
+
+    // Whether practice on each of the days `days` before today makes a
+    // week's streak.
+    fn week_streak(days: &[i64]) -> bool {
+        let mut history = History::default();
+        for &day in days {
+            history.push(&hit('a', -day * DAY_MS, 200));
+        }
+        Achievement::WeekStreak.is_earned(&context(&Stats::new(), &history))
+    }
+
+    #[test]
+    fn hundred_in_a_row_needs_an_unbroken_run() {
+        let mut events: Vec<HistoryEvent> = (0..99).map(|i| hit('a', i, 200)).collect();
+        events.push(miss('a', 99));
+        events.push(hit('a', 100, 200));
+        assert!(!earned_by(Achievement::HundredInARow, &events));
+
+        events.extend((101..200).map(|i| hit('a', i, 200)));
+        assert!(earned_by(Achievement::HundredInARow, &events));
+    }
+
+    #[test]
+    fn symbol_sprinter_needs_every_symbol_fast() {
+        let symbols = CharSet::Symbols.pool(Layout::Qwerty);
+        let mut events = hits(&symbols, 450);
+        assert!(earned_by(Achievement::SymbolSprinter, &events));
+
+        // One slow symbol pulls its average over the line.
+        events.push(HistoryEvent {
+            char_id: symbols[0],
+            result: HitType::Hit(at(0), 600),
+        });
+        assert!(!earned_by(Achievement::SymbolSprinter, &events));
+
+        // And every symbol has to be typed at all.
+        let missing_one = hits(&symbols[1..], 100);
+        assert!(!earned_by(Achievement::SymbolSprinter, &missing_one));
+    }
+
+    #[test]
+    fn week_streak_counts_practice_days_up_to_today() {
+        assert!(week_streak(&[0, 1, 2, 3, 4, 5, 6]));
+        // Today not practised yet doesn't break the run.
+        assert!(week_streak(&[1, 2, 3, 4, 5, 6, 7]));
+        assert!(!week_streak(&[0, 1, 2, 3, 4, 5]));
+        // A gap starts the count again.
+        assert!(!week_streak(&[0, 1, 2, 4, 5, 6, 7]));
+    }
+
+    #[test]
+    fn clean_sweep_needs_every_key_and_no_misses() {
+        let keys = CharSet::All.pool(Layout::Qwerty);
+        let mut events = hits(&keys, 300);
+        assert!(earned_by(Achievement::CleanSweep, &events));
+
+        events.push(miss('a', 0));
+        assert!(!earned_by(Achievement::CleanSweep, &events));
+        assert!(!earned_by(Achievement::CleanSweep, &hits(&keys[1..], 300)));
+    }
+
+    #[test]
+    fn evaluate_unlocks_each_achievement_once() {
+        let events: Vec<HistoryEvent> = (0..100).map(|i| hit('a', i, 200)).collect();
+        let stats = stats_of(&events);
+        let history = History::default();
+        let context = context(&stats, &history);
+        let mut achievements = Achievements::default();
+
+        let unlocked = achievements.evaluate(&context, at(1));
+        assert_eq!(unlocked, [Achievement::HundredInARow]);
+        assert!(achievements.evaluate(&context, at(2)).is_empty());
+        let unlocked_at = achievements.unlocked_at(Achievement::HundredInARow);
+        assert_eq!(unlocked_at, Some(at(1)));
+        assert_eq!(achievements.unlocked_at(Achievement::CleanSweep), None);
+    }
+}
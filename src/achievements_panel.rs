@@ -0,0 +1,31 @@
+use crate::achievements::{Achievement, Achievements};
+use leptos::prelude::*;
+
+#[component]
+pub fn AchievementGallery(achievements: ReadSignal<Achievements>) -> impl IntoView {
+    view! {
+        <div id="achievement-gallery">
+            {Achievement::ALL
+                .into_iter()
+                .map(|achievement| {
+                    let unlocked = move || achievements.get().unlocked_at(achievement);
+                    view! {
+                        <div
+                            class="achievement"
+                            id=format!("achievement-{}", achievement.id())
+                            class:locked=move || unlocked().is_none()>
+                            <strong>{achievement.label()}</strong>
+                            <p>{achievement.description()}</p>
+                            <p class="hint">
+                                {move || match unlocked() {
+                                    Some(at) => format!("Unlocked {}", at.format("%Y-%m-%d %H:%M")),
+                                    None => "Locked".to_string(),
+                                }}
+                            </p>
+                        </div>
+                    }
+                })
+                .collect_view()}
+        </div>
+    }
+}
@@ -1,50 +1,463 @@
-use crate::ascii_chars::AsciiChars;
-use crate::stats::Stats;
-use chrono::NaiveDateTime;
+use crate::achievements::{Achievement, Context};
+use crate::achievements_panel::AchievementGallery;
+use crate::backend::{self, SessionKey};
+use crate::calendar::PracticeCalendar;
+use crate::char_names::named_symbols;
+use crate::ghost::{Ghost, Standing};
+use crate::layout::{Layout, NUMPAD_CHARS};
+use crate::history::{self, legacy_events, DayTotal, History, HistoryEvent, LogFrame};
+use crate::history_panel::HistoryPanel;
+use crate::lessons::{builtin_lessons, Lesson, LessonMode, LessonProgress};
+use crate::personal_best::{generate_html_bests, Leaderboard, LeaderboardEntry, RecordKind};
+use crate::profile::{Profile, DEFAULT_PROFILE};
+use crate::race_panel::RacePanel;
+use crate::recording::{KeyEvent, Recording};
+use crate::replay::ReplayViewer;
+use crate::settings::{CharSet, DrillMode, ErrorPolicy, GhostKind, Settings};
+use crate::stats::{HitType, Stats};
+use crate::settings_panel::SettingsPanel;
+use crate::practice_text::split_passages;
+use crate::snippets::{bundled_snippets, pick_snippet, LineView, Snippet, SnippetDrill};
+use crate::sound::{self, Sound};
+use chrono::{NaiveDate, NaiveDateTime, Timelike};
 use leptos::task::spawn_local;
 use leptos::{
-    ev::{Event, SubmitEvent},
+    ev::{CompositionEvent, Event, KeyboardEvent, SubmitEvent},
     html,
     prelude::*,
 };
-// use rand::Rng;
-use crate::common::random_byte;
+use leptos::web_sys::InputEvent;
+use wasm_bindgen::JsCast;
+use crate::common::{glyph, now, random_seed, SeededRng};
 use crate::svg_keyboard::SvgKeyboard;
+use crate::target_char::TargetChar;
 use crate::timer::Timer;
-use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Duration;
 use std::sync::{Mutex, OnceLock};
-use wasm_bindgen::prelude::*;
 
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
-    async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+// Save the active profile every this many attempts.
+const AUTOSAVE_INTERVAL: usize = 20;
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+// How often to check whether a daily goal reminder is due.
+const REMINDER_CHECK: Duration = Duration::from_secs(60);
+// How often the ghost moves on between keystrokes.
+const GHOST_TICK: Duration = Duration::from_millis(100);
+// How many saved recordings to look through for the last session in a mode.
+const GHOST_SEARCH: usize = 20;
+
+fn rand_char(settings: &Settings) -> TargetChar {
+    let mut pool = match settings.drill_mode {
+        DrillMode::Lesson => current_lesson(settings)
+            .map(|lesson| lesson.pool())
+            .unwrap_or_default(),
+        DrillMode::Names => named_symbols(settings.layout),
+        _ => settings.char_set.pool(settings.layout),
+    };
+    if pool.is_empty() {
+        // e.g. accented characters on a layout without any.
+        pool = CharSet::All.pool(settings.layout);
+    }
+
+    pool[draw_index(pool.len())]
 }
 
-fn rand_char() -> &'static AsciiChars {
-    const LIST: [AsciiChars; 94] = AsciiChars::list();
+fn rand_numpad_char() -> char {
+    let keys: Vec<char> = NUMPAD_CHARS.chars().collect();
+    keys[draw_index(keys.len())]
+}
 
-    let index = random_byte(0, 93);
-    &LIST[index as usize]
+// A fresh target for the random and numpad drills.
+fn draw_char(settings: &Settings) -> char {
+    match settings.drill_mode {
+        DrillMode::Numpad => rand_numpad_char(),
+        _ => rand_char(settings).as_char(),
+    }
 }
 
-static STATS: OnceLock<Mutex<Stats>> = OnceLock::new();
+// Takes the next target from the look-ahead queue, topping the queue back up
+// so the upcoming characters are settled before they're due.
+fn next_queued_char(settings: &Settings) -> char {
+    let mut queue = UPCOMING.get().unwrap().lock().unwrap();
+    let next = queue.pop_front().unwrap_or_else(|| draw_char(settings));
+    while queue.len() < settings.look_ahead {
+        queue.push_back(draw_char(settings));
+    }
+    next
+}
+
+fn upcoming_chars(settings: &Settings) -> Vec<char> {
+    if is_line_drill(settings) {
+        SNIPPET_DRILL
+            .get()
+            .unwrap()
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|drill| drill.upcoming(settings.look_ahead))
+            .unwrap_or_default()
+    } else {
+        let queue = UPCOMING.get().unwrap().lock().unwrap();
+        queue.iter().take(settings.look_ahead).copied().collect()
+    }
+}
+
+// How a target appears in the prompt: by name in the name drill.
+fn prompt_text(settings: &Settings, c: char) -> String {
+    let name = target_char(settings, c).and_then(|target| target.name());
+    match (settings.drill_mode, name) {
+        (DrillMode::Names, Some(name)) => name,
+        _ => glyph(c).to_string(),
+    }
+}
+
+// Numpad drill targets are tracked apart from the same characters on the main
+// keys.
+fn target_char(settings: &Settings, c: char) -> Option<TargetChar> {
+    match settings.drill_mode {
+        DrillMode::Numpad => TargetChar::numpad(c),
+        _ => TargetChar::new(c),
+    }
+}
+
+static PROFILE: OnceLock<Mutex<Profile>> = OnceLock::new();
 static TIMER: OnceLock<Mutex<Timer>> = OnceLock::new();
 
 static HIGHLIGHTED_KEYS: OnceLock<Mutex<Vec<String>>> = OnceLock::new();
+static RECORDING: OnceLock<Mutex<Recording>> = OnceLock::new();
+// Practice totals for the calendar, goals and achievements.
+static HISTORY: OnceLock<Mutex<History>> = OnceLock::new();
+// Events of the current run, not yet saved.
+static SESSION_EVENTS: OnceLock<Mutex<Vec<HistoryEvent>>> = OnceLock::new();
+// Runs' events that the history log or database didn't take, to try again.
+static UNSAVED_BATCHES: OnceLock<Mutex<Vec<UnsavedBatch>>> = OnceLock::new();
+
+static LEADERBOARD: OnceLock<Mutex<Leaderboard>> = OnceLock::new();
+
+static LESSONS: OnceLock<Mutex<Vec<Lesson>>> = OnceLock::new();
+static SNIPPETS: OnceLock<Mutex<Vec<Snippet>>> = OnceLock::new();
+static PASSAGES: OnceLock<Mutex<Vec<String>>> = OnceLock::new();
+static SNIPPET_DRILL: OnceLock<Mutex<Option<SnippetDrill>>> = OnceLock::new();
+static UPCOMING: OnceLock<Mutex<VecDeque<char>>> = OnceLock::new();
+// Every random pick a drill makes, seeded from the recording so a ghost's
+// drill can be drawn again.
+static DRAWS: OnceLock<Mutex<SeededRng>> = OnceLock::new();
+static GHOST: OnceLock<Mutex<Option<Ghost>>> = OnceLock::new();
+
+fn draw_index(len: usize) -> usize {
+    DRAWS.get().unwrap().lock().unwrap().index(len)
+}
+
+// Starts the draws over from the recording's seed, so a restarted drill
+// comes out the same.
+fn reseed_draws() {
+    let seed = RECORDING.get().unwrap().lock().unwrap().seed.unwrap_or_default();
+    *DRAWS.get().unwrap().lock().unwrap() = SeededRng::new(seed);
+}
+
+fn current_lesson(settings: &Settings) -> Option<Lesson> {
+    let lessons = LESSONS.get().unwrap().lock().unwrap();
+    match settings.lesson.as_deref() {
+        Some(id) => lessons.iter().find(|lesson| lesson.id == id).cloned(),
+        None => PROFILE
+            .get()
+            .unwrap()
+            .lock()
+            .unwrap()
+            .lessons
+            .next_lesson(&lessons)
+            .cloned(),
+    }
+}
+
+fn is_line_drill(settings: &Settings) -> bool {
+    match settings.drill_mode {
+        DrillMode::Code | DrillMode::Text => true,
+        DrillMode::Lesson => {
+            current_lesson(settings).is_some_and(|lesson| lesson.mode == LessonMode::Text)
+        }
+        DrillMode::Random | DrillMode::Numpad | DrillMode::Names => false,
+    }
+}
+
+// Where text passages are loaded from: a file or folder picked in settings,
+// or one in the lessons folder that a lesson names.
+#[derive(Debug, Clone, PartialEq)]
+enum TextSource {
+    Picked(String),
+    Lesson(String),
+}
+
+fn text_source(settings: &Settings) -> Option<TextSource> {
+    match settings.drill_mode {
+        DrillMode::Lesson => current_lesson(settings)
+            .and_then(|lesson| lesson.text_source)
+            .map(TextSource::Lesson),
+        _ => settings.text_source.clone().map(TextSource::Picked),
+    }
+}
+
+fn lesson_progress() -> LessonProgress {
+    PROFILE.get().unwrap().lock().unwrap().lessons.clone()
+}
+
+// Scores the finished run against the lesson's goal and returns whether it
+// passed, with what to tell the user.
+fn finish_lesson(lesson: &Lesson) -> (bool, String) {
+    let now = chrono::Local::now().naive_local();
+    let Ok(mut profile) = PROFILE.get().unwrap().lock() else {
+        return (false, String::new());
+    };
+    let run = profile.stats.get_run_summary();
+    if lesson.passed_by(&run) {
+        profile.lessons.record(lesson, &run, now);
+        let outcome = format!(
+            "Lesson passed with {:.0}% accuracy and a {} ms average!",
+            run.accuracy, run.hit_avg
+        );
+        (true, outcome)
+    } else {
+        let outcome = format!(
+            "Not yet: {:.0}% accuracy, {} ms average. {}",
+            run.accuracy,
+            run.hit_avg,
+            lesson.goal()
+        );
+        (false, outcome)
+    }
+}
+
+// Unlocks any achievements earned since the last check and returns them.
+fn check_achievements() -> Vec<Achievement> {
+    let now = chrono::Local::now().naive_local();
+    let history = HISTORY.get().unwrap().lock().unwrap();
+    let Ok(mut profile) = PROFILE.get().unwrap().lock() else {
+        return Vec::new();
+    };
+    let profile = &mut *profile;
+    let context = Context {
+        stats: &profile.stats,
+        history: &history,
+        layout: profile.settings.layout,
+        today: now.date(),
+    };
+    profile.achievements.evaluate(&context, now)
+}
+
+fn current_mode() -> String {
+    PROFILE.get().unwrap().lock().unwrap().settings.mode_key()
+}
+
+fn add_hit(char_id: TargetChar, timestamp: NaiveDateTime, reaction_time: u64) {
+    if let Ok(mut profile) = PROFILE.get().unwrap().lock() {
+        profile.stats.add_hit(char_id, timestamp, reaction_time);
+    }
+    add_history(char_id, HitType::Hit(timestamp, reaction_time));
+}
+
+fn add_miss(char_id: TargetChar, timestamp: NaiveDateTime) {
+    if let Ok(mut profile) = PROFILE.get().unwrap().lock() {
+        profile.stats.add_miss(char_id, timestamp);
+    }
+    add_history(char_id, HitType::Miss(timestamp));
+}
+
+fn add_history(char_id: TargetChar, result: HitType) {
+    let event = HistoryEvent { char_id, result };
+    if let Ok(mut history) = HISTORY.get().unwrap().lock() {
+        history.push(&event);
+    }
+    if let Ok(mut events) = SESSION_EVENTS.get().unwrap().lock() {
+        events.push(event);
+    }
+}
+
+fn today_total() -> DayTotal {
+    let today = chrono::Local::now().date_naive();
+    HISTORY.get().unwrap().lock().unwrap().day_total(today)
+}
+
+// Takes the events the database hasn't seen, with the run they belong to.
+// Done before the recording can be restarted for the next run.
+fn take_session_events() -> Option<(SessionKey, Vec<HistoryEvent>)> {
+    let events = std::mem::take(&mut *SESSION_EVENTS.get().unwrap().lock().unwrap());
+    if events.is_empty() {
+        return None;
+    }
+    let recording = RECORDING.get().unwrap().lock().unwrap();
+    let session = SessionKey {
+        started: recording.started,
+        mode: recording.mode.clone(),
+    };
+    Some((session, events))
+}
+
+// A run's events on their way to the history log and then the database.
+#[derive(Debug)]
+struct UnsavedBatch {
+    profile: String,
+    session: SessionKey,
+    events: Vec<HistoryEvent>,
+    /// Where the log took them, once it has
+    offset: Option<u64>,
+}
+
+async fn save_batch(batch: &mut UnsavedBatch) -> Result<(), String> {
+    let offset = match batch.offset {
+        Some(offset) => offset,
+        None => {
+            let frame = history::encode_frame(&batch.events);
+            let offset =
+                backend::append_history(&batch.profile, &history::header(), &frame).await?;
+            *batch.offset.insert(offset)
+        }
+    };
+    backend::add_session_events(&batch.profile, &batch.session, offset, &batch.events).await
+}
+
+// Saves a run's events along with any earlier ones that didn't make it. What
+// fails is kept for next time; the log offset stops a batch the database
+// already took from being added twice.
+async fn save_session_events(name: &str, session: Option<(SessionKey, Vec<HistoryEvent>)>) {
+    let mut batches = std::mem::take(&mut *UNSAVED_BATCHES.get().unwrap().lock().unwrap());
+    if let Some((session, events)) = session {
+        batches.push(UnsavedBatch {
+            profile: name.to_string(),
+            session,
+            events,
+            offset: None,
+        });
+    }
+    let mut unsaved = Vec::new();
+    for mut batch in batches {
+        if save_batch(&mut batch).await.is_err() {
+            unsaved.push(batch);
+        }
+    }
+    UNSAVED_BATCHES
+        .get()
+        .unwrap()
+        .lock()
+        .unwrap()
+        .extend(unsaved);
+}
+
+// Brings the database up to date with the profile's history log: a damaged
+// tail is cut off, a new log starts with the events in the profile's saved
+// stats, and any frame the database doesn't have yet is added to it.
+async fn index_history(name: &str, stats: &Stats) {
+    let Ok(bytes) = backend::load_history(name).await else {
+        return;
+    };
+    let mut log = match history::decode(&bytes) {
+        Ok(log) => log,
+        // Left alone for a newer version of the app to read.
+        Err(_) => return,
+    };
+    if log.valid_len < bytes.len()
+        && backend::truncate_history(name, log.valid_len)
+            .await
+            .is_err()
+    {
+        return;
+    }
+    if log.frames.is_empty() {
+        let events = legacy_events(stats);
+        if !events.is_empty() {
+            let frame = history::encode_frame(&events);
+            let Ok(offset) = backend::append_history(name, &history::header(), &frame).await else {
+                return;
+            };
+            log.frames.push(LogFrame { offset, events });
+        }
+    }
+    let Ok(indexed) = backend::indexed_frames(name).await else {
+        return;
+    };
+    let missing: Vec<LogFrame> = log
+        .frames
+        .into_iter()
+        .filter(|frame| !indexed.contains(&frame.offset))
+        .collect();
+    if !missing.is_empty() {
+        let _ = backend::index_frames(name, &missing).await;
+    }
+}
 
-fn add_hit(char_id: AsciiChars, timestamp: NaiveDateTime, reaction_time: u64) {
-    if let Ok(mut stats) = STATS.get().unwrap().lock() {
-        stats.add_hit(char_id, timestamp, reaction_time);
+// Reads the profile's practice totals from the database, once it has caught
+// up with the history log.
+async fn load_history(name: &str, stats: Stats) {
+    save_session_events(name, None).await;
+    index_history(name, &stats).await;
+    let mut history = match backend::daily_aggregates(name, None).await {
+        Ok(days) => History::from_days(&days),
+        Err(e) => History::unavailable(e),
+    };
+    // Anything typed while loading, or that the log couldn't take, isn't in
+    // the database yet.
+    let unsaved = UNSAVED_BATCHES.get().unwrap().lock().unwrap();
+    let unlogged = unsaved
+        .iter()
+        .filter(|batch| batch.profile == name && batch.offset.is_none())
+        .flat_map(|batch| &batch.events);
+    for event in unlogged.chain(SESSION_EVENTS.get().unwrap().lock().unwrap().iter()) {
+        history.push(event);
     }
+    *HISTORY.get().unwrap().lock().unwrap() = history;
 }
 
-fn add_miss(char_id: AsciiChars, timestamp: NaiveDateTime) {
-    if let Ok(mut stats) = STATS.get().unwrap().lock() {
-        stats.add_miss(char_id, timestamp);
+fn add_shift_use(opposite_hand: bool) {
+    if let Ok(mut profile) = PROFILE.get().unwrap().lock() {
+        profile.stats.add_shift_use(opposite_hand);
     }
 }
 
+// A run raced against a ghost is drawn from the ghost's seed.
+fn new_recording(settings: &Settings) -> Recording {
+    let mode = settings.mode_key();
+    let seed = GHOST
+        .get()
+        .unwrap()
+        .lock()
+        .unwrap()
+        .as_ref()
+        .filter(|ghost| ghost.mode == mode)
+        .map_or_else(random_seed, |ghost| ghost.seed);
+    Recording::new(
+        mode,
+        settings.layout,
+        seed,
+        chrono::Local::now().naive_local(),
+        now(),
+    )
+}
+
+// Stamps an event with the time since the recording started and logs it.
+fn record(event: impl FnOnce(u64) -> KeyEvent) {
+    if let Ok(mut recording) = RECORDING.get().unwrap().lock() {
+        let at_ms = recording.elapsed_ms(now());
+        recording.push(event(at_ms));
+    }
+}
+
+fn save_recording() {
+    let mut recording = RECORDING.get().unwrap().lock().unwrap();
+    if !recording.has_attempts() {
+        return;
+    }
+    let save = backend::save_recording(&PROFILE.get().unwrap().lock().unwrap().name, &mut recording);
+    spawn_local(async move {
+        let _ = save.await;
+    });
+}
+
+// Saves the run's recording and starts a fresh one.
+fn restart_recording() {
+    save_recording();
+    let settings = PROFILE.get().unwrap().lock().unwrap().settings.clone();
+    *RECORDING.get().unwrap().lock().unwrap() = new_recording(&settings);
+}
+
 fn restart_timer() {
     if let Ok(mut timer) = TIMER.get().unwrap().lock() {
         timer.restart();
@@ -92,6 +505,231 @@ fn clear_highlighted_keys() {
     }
 }
 
+fn attempt_count() -> usize {
+    let profile = PROFILE.get().unwrap().lock().unwrap();
+    profile.stats.get_total_hit_count() + profile.stats.get_total_miss_count()
+}
+
+fn save_profile() {
+    let (save, name) = {
+        let profile = PROFILE.get().unwrap().lock().unwrap();
+        (backend::save_profile(&profile), profile.name.clone())
+    };
+    let session = take_session_events();
+    spawn_local(async move {
+        let _ = save.await;
+        save_session_events(&name, session).await;
+    });
+}
+
+fn update_personal_bests() -> Vec<RecordKind> {
+    let now = chrono::Local::now().naive_local();
+    let recording_id = {
+        let recording = RECORDING.get().unwrap().lock().unwrap();
+        recording.seed.map(|_| recording.id())
+    };
+
+    let Ok(mut profile) = PROFILE.get().unwrap().lock() else {
+        return Vec::new();
+    };
+    let mode = profile.settings.mode_key();
+    let run = profile.stats.get_run_summary();
+    let new_records = profile.bests.update(&mode, &run, now);
+    if new_records.is_empty() {
+        return new_records;
+    }
+    // The fastest run is the one raced as the personal best ghost.
+    if let (true, Some(mut best)) = (
+        new_records.contains(&RecordKind::FastestAverage),
+        profile.bests.get(&mode).cloned(),
+    ) {
+        best.fastest_recording = recording_id;
+        profile.bests.set(&mode, best);
+    }
+
+    let entry = LeaderboardEntry {
+        profile: profile.name.clone(),
+        best: profile.bests.get(&mode).cloned().unwrap_or_default(),
+        mode,
+    };
+    if let Ok(mut board) = LEADERBOARD.get().unwrap().lock() {
+        board.upsert(entry.clone());
+    }
+    spawn_local(async move {
+        let _ = backend::submit_leaderboard_entry(&entry).await;
+    });
+
+    new_records
+}
+
+// The run just finished becomes the ghost when it's the one to race: the
+// last session always, the personal best when it set the fastest average.
+fn keep_ghost() {
+    let recording = RECORDING.get().unwrap().lock().unwrap();
+    let (kind, fastest) = {
+        let profile = PROFILE.get().unwrap().lock().unwrap();
+        let fastest = profile
+            .bests
+            .get(&recording.mode)
+            .and_then(|best| best.fastest_recording.clone());
+        (profile.settings.ghost, fastest)
+    };
+    let keep = match kind {
+        GhostKind::Off => false,
+        GhostKind::Best => fastest == Some(recording.id()),
+        GhostKind::Last => true,
+    };
+    if let (true, Some(ghost)) = (keep, Ghost::from_recording(&recording, kind)) {
+        *GHOST.get().unwrap().lock().unwrap() = Some(ghost);
+    }
+}
+
+// Finds the saved run to race for the current mode.
+async fn find_ghost(profile: &str, settings: &Settings) -> Option<Ghost> {
+    let mode = settings.mode_key();
+    match settings.ghost {
+        GhostKind::Off => None,
+        GhostKind::Best => {
+            let id = PROFILE
+                .get()
+                .unwrap()
+                .lock()
+                .unwrap()
+                .bests
+                .get(&mode)?
+                .fastest_recording
+                .clone()?;
+            let recording = backend::load_recording(profile, &id).await.ok()??;
+            Ghost::from_recording(&recording, GhostKind::Best)
+        }
+        GhostKind::Last => {
+            let current = RECORDING.get().unwrap().lock().unwrap().id();
+            let ids = backend::list_recordings(profile).await.ok()?;
+            for id in ids.into_iter().filter(|id| *id != current).take(GHOST_SEARCH) {
+                let Ok(Some(recording)) = backend::load_recording(profile, &id).await else {
+                    continue;
+                };
+                if recording.mode != mode {
+                    continue;
+                }
+                if let Some(ghost) = Ghost::from_recording(&recording, GhostKind::Last) {
+                    return Some(ghost);
+                }
+            }
+            None
+        }
+    }
+}
+
+// How the finished run did against its ghost.
+fn ghost_outcome() -> Option<String> {
+    let ghost = GHOST.get().unwrap().lock().unwrap();
+    let ghost = ghost.as_ref()?;
+    let recording = RECORDING.get().unwrap().lock().unwrap();
+    if !ghost.races(&recording) {
+        return None;
+    }
+    let name = ghost.kind.label().to_lowercase();
+    let outcome = match ghost.lead_ms(&recording)? {
+        0 => format!("Dead heat with your {} ghost.", name),
+        lead if lead > 0 => format!("You beat your {} ghost by {} ms!", name, lead),
+        lead => format!("Your {} ghost won by {} ms.", name, -lead),
+    };
+    Some(outcome)
+}
+
+fn start_line_drill(settings: &Settings) -> Option<char> {
+    let drill = match settings.drill_mode {
+        DrillMode::Random | DrillMode::Numpad | DrillMode::Names => return None,
+        DrillMode::Lesson => {
+            let lesson = current_lesson(settings)?;
+            let passages: Vec<String> = if lesson.texts.is_empty() {
+                PASSAGES.get().unwrap().lock().unwrap().clone()
+            } else {
+                lesson
+                    .texts
+                    .iter()
+                    .flat_map(|text| split_passages(text, &settings.layout))
+                    .collect()
+            };
+            if passages.is_empty() {
+                return None;
+            }
+            SnippetDrill::from_passage(&lesson.title, &passages[draw_index(passages.len())])
+        }
+        DrillMode::Code => {
+            let snippet = pick_snippet(
+                &SNIPPETS.get().unwrap().lock().unwrap(),
+                settings.code_language,
+                draw_index,
+            )?;
+            SnippetDrill::new(&snippet, settings.skip_indent)
+        }
+        DrillMode::Text => {
+            let passages = PASSAGES.get().unwrap().lock().unwrap();
+            if passages.is_empty() {
+                return None;
+            }
+            let title = settings.text_source.clone().unwrap_or_default();
+            SnippetDrill::from_passage(&title, &passages[draw_index(passages.len())])
+        }
+    };
+    let target = drill.target();
+    *SNIPPET_DRILL.get().unwrap().lock().unwrap() = Some(drill);
+    target
+}
+
+fn advance_line_drill(settings: &Settings) -> Option<char> {
+    let advanced = SNIPPET_DRILL
+        .get()
+        .unwrap()
+        .lock()
+        .unwrap()
+        .as_mut()
+        .is_some_and(|drill| drill.advance());
+    if advanced {
+        SNIPPET_DRILL
+            .get()
+            .unwrap()
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|drill| drill.target())
+    } else {
+        // Finished (or never started), so move on to another snippet.
+        start_line_drill(settings)
+    }
+}
+
+fn mark_line_error() {
+    if let Some(drill) = SNIPPET_DRILL.get().unwrap().lock().unwrap().as_mut() {
+        drill.mark_error();
+    }
+}
+
+fn snippet_line_view() -> LineView {
+    SNIPPET_DRILL
+        .get()
+        .unwrap()
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|drill| drill.line_view())
+        .unwrap_or_default()
+}
+
+async fn load_passages(source: &TextSource, layout: Layout) {
+    let text = match source {
+        TextSource::Picked(path) => backend::load_practice_text(path).await,
+        TextSource::Lesson(path) => backend::load_lesson_text(path).await,
+    };
+    let passages = match text {
+        Ok(practice_text) => split_passages(&practice_text.text, &layout),
+        Err(_) => Vec::new(),
+    };
+    *PASSAGES.get().unwrap().lock().unwrap() = passages;
+}
+
 fn take_highlighted_key() -> Option<String> {
     if let Some(lock) = HIGHLIGHTED_KEYS.get() {
         if let Ok(mut keys) = lock.lock() {
@@ -102,17 +740,58 @@ fn take_highlighted_key() -> Option<String> {
 }
 
 #[component]
-pub fn App() -> impl IntoView {
-    STATS.set(Mutex::new(Stats::new())).unwrap();
+pub fn App(profile: Profile) -> impl IntoView {
+    let (settings, set_settings) = signal(profile.settings.clone());
+    let (profile_names, set_profile_names) = signal(vec![profile.name.clone()]);
+    let (active_profile, set_active_profile) = signal(profile.name.clone());
+    let (lessons, set_lessons) = signal(builtin_lessons());
+    let (lesson_errors, set_lesson_errors) = signal(Vec::<String>::new());
+    let (run_started, set_run_started) = signal(None::<u64>);
+    let (progress, set_progress) = signal(profile.lessons.clone());
+    let (lesson_outcome, set_lesson_outcome) = signal(None::<String>);
+    let (achievements, set_achievements) = signal(profile.achievements.clone());
+    // Achievements just unlocked, shown briefly; numbered so each can be
+    // dismissed on its own.
+    let (toasts, set_toasts) = signal(Vec::<(usize, Achievement)>::new());
+    let toast_count = StoredValue::new(0usize);
+    let (today, set_today) = signal(DayTotal::default());
+    // The last day a goal reminder went out, so there's one a day at most.
+    let reminded = StoredValue::new(None::<NaiveDate>);
+    let (new_profile_name, set_new_profile_name) = signal(String::new());
+    let (profile_error, set_profile_error) = signal(None::<String>);
+
+    GHOST.set(Mutex::new(None)).unwrap();
+    DRAWS.set(Mutex::new(SeededRng::new(0))).unwrap();
+    RECORDING.set(Mutex::new(new_recording(&profile.settings))).unwrap();
+    HISTORY.set(Mutex::new(History::default())).unwrap();
+    SESSION_EVENTS.set(Mutex::new(Vec::new())).unwrap();
+    UNSAVED_BATCHES.set(Mutex::new(Vec::new())).unwrap();
+    // Stats a profile was saved with are history from before the log, not
+    // part of the run about to start.
+    let mut profile = profile;
+    let stats = std::mem::replace(&mut profile.stats, Stats::new());
+    let profile_name = profile.name.clone();
+    spawn_local(async move {
+        load_history(&profile_name, stats).await;
+        set_today.set(today_total());
+    });
+    PROFILE.set(Mutex::new(profile)).unwrap();
     TIMER.set(Mutex::new(Timer::new_started())).unwrap();
     HIGHLIGHTED_KEYS.set(Mutex::new(Vec::new())).unwrap();
+    LESSONS.set(Mutex::new(lessons.get_untracked())).unwrap();
+    SNIPPETS.set(Mutex::new(bundled_snippets())).unwrap();
+    PASSAGES.set(Mutex::new(Vec::new())).unwrap();
+    SNIPPET_DRILL.set(Mutex::new(None)).unwrap();
+    UPCOMING.set(Mutex::new(VecDeque::new())).unwrap();
+    LEADERBOARD.set(Mutex::new(Leaderboard::default())).unwrap();
 
-    // let (greet_msg, set_greet_msg) = signal(String::new());
-    //
-    // let update_name = move |ev| {
-    //     let v = event_target_value(&ev);
-    //     set_name.set(v);
-    // };
+    spawn_local(async move {
+        if let Ok(leaderboard) = backend::load_leaderboard().await {
+            if let Ok(mut board) = LEADERBOARD.get().unwrap().lock() {
+                *board = leaderboard;
+            }
+        }
+    });
 
     let input_ref = NodeRef::<html::Input>::new();
     let hit_ref = NodeRef::<html::P>::new();
@@ -120,82 +799,209 @@ pub fn App() -> impl IntoView {
     let report_ref = NodeRef::<html::Div>::new();
     let svg_keyboard_ref = NodeRef::<html::Div>::new();
 
-    let (the_char, set_the_char) = signal(rand_char().as_char().to_string());
-    let (keyboard_visible, set_keyboard_visible) = signal(false);
+    let (the_char, set_the_char) = signal(String::new());
+    let (upcoming, set_upcoming) = signal(Vec::<char>::new());
+    // A wrong character left in the box for the user to delete.
+    let (uncorrected, set_uncorrected) = signal(false);
+    // The last target passed over after a miss.
+    let (missed, set_missed) = signal(None::<char>);
+    // Whether the last attempt was a hit, shown as a mark beside the input.
+    let (last_result, set_last_result) = signal(None::<bool>);
+    // Read out by screen readers whenever it changes.
+    let (announcement, set_announcement) = signal(String::new());
+    let announce_count = StoredValue::new(0usize);
+    let (key_hint, set_key_hint) = signal(None::<String>);
+    let (finger_hint, set_finger_hint) = signal(None::<String>);
+    let (line_view, set_line_view) = signal(LineView::default());
+    let (settings_open, set_settings_open) = signal(false);
+    let (replay_open, set_replay_open) = signal(false);
+    let (history_open, set_history_open) = signal(false);
+    let (gallery_open, set_gallery_open) = signal(false);
+    let (calendar_open, set_calendar_open) = signal(false);
+    let (race_open, set_race_open) = signal(false);
+    let (test_complete, set_test_complete) = signal(false);
+    let (ghost_standing, set_ghost_standing) = signal(None::<Standing>);
+    let (ghost_result, set_ghost_result) = signal(None::<String>);
 
-    let toggle_keyboard = move |_: Event| {
-        set_keyboard_visible.update(|visible| *visible = !*visible);
+    // Live regions stay quiet when the text doesn't change, so alternate a
+    // zero-width space on the end to have a repeat read out too.
+    let announce = move |text: String| {
+        let count = announce_count.get_value() + 1;
+        announce_count.set_value(count);
+        let padding = if count % 2 == 0 { "\u{200B}" } else { "" };
+        set_announcement.set(format!("{}{}", text, padding));
     };
 
-    let next_char = move || {
-        let char = rand_char();
-        set_the_char.set(char.as_char().to_string());
+    let show_target = move |target: Option<char>| {
+        let settings = settings.get_untracked();
+        let target = target.and_then(|c| target_char(&settings, c));
+        if let Some(target) = target {
+            record(|at_ms| KeyEvent::Target { at_ms, target });
+            announce(target.spoken_name());
+        }
+        set_the_char.set(target.map(|t| t.as_char().to_string()).unwrap_or_default());
+        set_key_hint.set(
+            target
+                .filter(|t| !t.is_numpad())
+                .and_then(|t| settings.layout.describe(t.as_char())),
+        );
+        set_finger_hint.set(
+            target
+                .filter(|t| !t.is_numpad())
+                .and_then(|t| settings.layout.finger_hint(t.as_char())),
+        );
+        set_line_view.set(snippet_line_view());
+        let next = upcoming_chars(&settings);
+        let next_target = next.first().and_then(|&c| target_char(&settings, c));
+        set_upcoming.set(next);
         svg_keyboard_ref.get().map(|el| {
             if let Some(doc) = el.owner_document() {
                 // Remove previous highlights
                 while let Some(key) = take_highlighted_key() {
                     if let Some(el2) = doc.get_element_by_id(&key) {
-                        let _ = el2.class_list().remove_1("key-highlight");
+                        let _ = el2.class_list().remove_2("key-highlight", "key-next");
                     }
                 }
-                // Highlight the new character
-                let (kb_id, kb_shift) = SvgKeyboard::get_css_ids(&char);
-
-                if let Some(el) = doc.get_element_by_id(&kb_id) {
-                    add_highlighted_key(&kb_id);
-                    let _ = el.class_list().add_1("key-highlight");
+                // Dim the key coming up after this one
+                if let Some(next) = next_target {
+                    for kb_id in SvgKeyboard::get_css_ids(&next, settings.layout) {
+                        if let Some(el) = doc.get_element_by_id(kb_id) {
+                            add_highlighted_key(kb_id);
+                            let _ = el.class_list().add_1("key-next");
+                        }
+                    }
                 }
-
-                if let Some(kb_shift) = kb_shift {
-                    if let Some(el) = doc.get_element_by_id(&kb_shift) {
-                        add_highlighted_key(&kb_shift);
+                // Highlight the new character
+                let Some(char) = target else {
+                    return;
+                };
+                for kb_id in SvgKeyboard::get_css_ids(&char, settings.layout) {
+                    if let Some(el) = doc.get_element_by_id(kb_id) {
+                        add_highlighted_key(kb_id);
                         let _ = el.class_list().add_1("key-highlight");
                     }
                 }
             }
         });
     };
-    let (theirs, set_theirs) = signal(String::new());
-    // let greet = move |ev: SubmitEvent| {
-    //     ev.prevent_default();
-    //     spawn_local(async move {
-    //         let name = name.get_untracked();
-    //         if name.is_empty() {
-    //             return;
-    //         }
-    //
-    //         let args = serde_wasm_bindgen::to_value(&GreetArgs { name: &name }).unwrap();
-    //         // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-    //         let new_msg = invoke("greet", args).await.as_string().unwrap();
-    //         set_greet_msg.set(new_msg);
-    //         next_char();
-    //     });
-    // };
-
-    // view! {
-    //     <main class="container">
-    //
-    //         <form class="row" on:submit=greet>
-    //             <input
-    //                 id="greet-input"
-    //                 placeholder="Enter a name..."
-    //                 on:input=update_name
-    //             />
-    //             <button type="submit">"Greet"</button>
-    //         </form>
-    //         <p>{ move || greet_msg.get() }</p>
-    //         <p>{ move || the_char.get() }</p>
-    //     </main>
-    // }
 
+    let next_char = move || {
+        let settings = settings.get_untracked();
+        let target = if is_line_drill(&settings) {
+            advance_line_drill(&settings)
+        } else {
+            Some(next_queued_char(&settings))
+        };
+        show_target(target);
+    };
+
+    // Throws away the drill in progress and starts a fresh one.
+    let restart_drill = move || {
+        let settings = settings.get_untracked();
+        reseed_draws();
+        UPCOMING.get().unwrap().lock().unwrap().clear();
+        set_uncorrected.set(false);
+        set_missed.set(None);
+        set_last_result.set(None);
+        if let Some(input) = input_ref.get_untracked() {
+            input.set_value("");
+        }
+        let target = if is_line_drill(&settings) {
+            start_line_drill(&settings)
+        } else {
+            Some(next_queued_char(&settings))
+        };
+        show_target(target);
+    };
+    restart_drill();
+
+    // Moves the ghost on; it only shows while the run is on its characters.
+    let refresh_ghost = move || {
+        let ghost = GHOST.get().unwrap().lock().unwrap();
+        let recording = RECORDING.get().unwrap().lock().unwrap();
+        let standing = ghost
+            .as_ref()
+            .filter(|ghost| ghost.races(&recording))
+            .map(|ghost| ghost.standing(&recording, now(), test_complete.get_untracked()));
+        if standing != ghost_standing.get_untracked() {
+            set_ghost_standing.set(standing);
+        }
+    };
+    set_interval(refresh_ghost, GHOST_TICK);
+
+    // Looks up the ghost for the current settings, and if nothing's been
+    // typed yet, starts the run over on its characters.
+    let load_ghost = move || {
+        let settings = settings.get_untracked();
+        if settings.ghost == GhostKind::Off {
+            *GHOST.get().unwrap().lock().unwrap() = None;
+            refresh_ghost();
+            return;
+        }
+        spawn_local(async move {
+            let name = PROFILE.get().unwrap().lock().unwrap().name.clone();
+            let ghost = find_ghost(&name, &settings).await;
+            let found = ghost.is_some();
+            *GHOST.get().unwrap().lock().unwrap() = ghost;
+            let fresh = !RECORDING.get().unwrap().lock().unwrap().has_attempts();
+            if found && fresh {
+                *RECORDING.get().unwrap().lock().unwrap() = new_recording(&settings);
+                restart_drill();
+            }
+            refresh_ghost();
+        });
+    };
+    load_ghost();
+
+    let (theirs, set_theirs) = signal(String::new());
+    // Whether the last key pressed was on the numeric keypad.
+    let (numpad_key, set_numpad_key) = signal(false);
+    // The Shift key being held, as its keyboard id.
+    let (shift_held, set_shift_held) = signal(None::<&'static str>);
     // Focus the input on component mount
     Effect::new(move |_| {
         if let Some(input) = input_ref.get() {
             let _ = input.focus();
         }
     });
-    let check_result = move |ev: SubmitEvent| {
-        ev.prevent_default();
+    let refresh_counters = move || {
+        let (current_hits, current_misses) = {
+            let profile = PROFILE.get().unwrap().lock().unwrap();
+            (
+                profile.stats.get_total_hit_count(),
+                profile.stats.get_total_miss_count(),
+            )
+        };
+        if let Some(hit) = hit_ref.get() {
+            hit.set_inner_text(&format!("Hits: {}", current_hits));
+        }
+        if let Some(miss) = miss_ref.get() {
+            miss.set_inner_text(&format!("Misses: {}", current_misses));
+        }
+    };
+
+    let show_report = move || {
+        let new_records = update_personal_bests();
+
+        if let Some(report) = report_ref.get() {
+            let mode = current_mode();
+            let profile = PROFILE.get().unwrap().lock().unwrap();
+            let best = profile.bests.get(&mode).cloned().unwrap_or_default();
+            let mut report_content = profile.stats.generate_html_report();
+            report_content.push_str(&HISTORY.get().unwrap().lock().unwrap().generate_html_summary());
+            report_content.push_str(&generate_html_bests(&best, &new_records));
+
+            if let Ok(board) = LEADERBOARD.get().unwrap().lock() {
+                report_content.push_str(&board.generate_html_report(&mode, &profile.name));
+            }
+
+            report.set_inner_html(&report_content);
+        }
+        save_profile();
+        new_records
+    };
+
+    let check_input = move || {
         spawn_local(async move {
             let want_char = the_char.get_untracked();
             if want_char.is_empty() {
@@ -203,144 +1009,524 @@ pub fn App() -> impl IntoView {
             }
 
             let theirs = theirs.get_untracked();
-            if theirs.is_empty() {
+            if theirs.is_empty() || uncorrected.get_untracked() {
                 return;
             }
 
             let their_char = theirs.chars().nth(0).unwrap();
             let want_char = want_char.chars().nth(0).unwrap();
+            let target = target_char(&settings.get_untracked(), want_char);
+            // The right character from the wrong key (top-row 7 for keypad 7)
+            // still counts as a miss.
+            let right_key = !target.is_some_and(|t| t.is_numpad()) || numpad_key.get_untracked();
+            let hit = their_char == want_char && right_key;
+            if let Some(target) = target {
+                record(|at_ms| KeyEvent::Typed {
+                    at_ms,
+                    typed: their_char,
+                    target,
+                    hit,
+                });
+            }
 
-            if their_char == want_char {
+            let policy = settings.get_untracked().error_policy;
+            sound::play(
+                &settings.get_untracked(),
+                if hit { Sound::Hit } else { Sound::Miss },
+            );
+            set_last_result.set(Some(hit));
+            if hit {
+                set_missed.set(None);
                 let ms = stop_timer();
                 let now = chrono::Local::now().naive_local();
-                add_hit(AsciiChars::from_char(want_char).unwrap(), now, ms);
+                // A reaction slower than the idle timeout means the user stepped
+                // away, so it isn't held against them.
+                let idle = settings
+                    .get_untracked()
+                    .idle_timeout_ms()
+                    .is_some_and(|timeout| ms > timeout);
+                if let (false, Some(char_id)) = (idle, target) {
+                    add_hit(char_id, now, ms);
+                }
+                // Technique is only measured when we saw which Shift was used.
+                let expected_shift = target
+                    .filter(|t| !t.is_numpad())
+                    .and_then(|t| settings.get_untracked().layout.shift_key(t.as_char()));
+                if let (Some(expected), Some(held)) = (expected_shift, shift_held.get_untracked()) {
+                    add_shift_use(held == expected);
+                }
 
                 next_char();
-                // Clear DOM input
-                if let Some(input) = input_ref.get() {
-                    input.set_value("");
+            } else if let Some(char_id) = target {
+                let now = chrono::Local::now().naive_local();
+                add_miss(char_id, now);
+                match policy {
+                    ErrorPolicy::Stop => {}
+                    ErrorPolicy::Lenient => {
+                        set_missed.set(Some(want_char));
+                        mark_line_error();
+                        next_char();
+                    }
+                    ErrorPolicy::Correct => set_uncorrected.set(true),
                 }
+                // Says what's wanted now: the same target, or the next one.
+                let wanted = the_char
+                    .get_untracked()
+                    .chars()
+                    .next()
+                    .and_then(|c| target_char(&settings.get_untracked(), c))
+                    .map(|t| t.spoken_name())
+                    .unwrap_or_default();
+                announce(match policy {
+                    ErrorPolicy::Correct => format!("Miss. Backspace, then {}", wanted),
+                    ErrorPolicy::Lenient => format!("Missed {}. Next {}", char_id.spoken_name(), wanted),
+                    ErrorPolicy::Stop => format!("Miss. {}", wanted),
+                });
+            }
 
-                // Update hit count
-                if let Some(hit) = hit_ref.get() {
-                    let current_hits = STATS.get().unwrap().lock().unwrap().get_total_hit_count();
-                    let current_hits = format!("Hits: {}", current_hits);
+            // The wrong character stays put until it's deleted.
+            let keep_input = !hit && policy == ErrorPolicy::Correct;
+            if let (false, Some(input)) = (keep_input, input_ref.get()) {
+                input.set_value("");
+            }
+            refresh_counters();
+            set_today.set(today_total());
+            refresh_ghost();
 
-                    hit.set_inner_text(&current_hits);
+            let unlocked = check_achievements();
+            if !unlocked.is_empty() {
+                set_achievements.set(PROFILE.get().unwrap().lock().unwrap().achievements.clone());
+                for achievement in unlocked {
+                    let id = toast_count.get_value();
+                    toast_count.set_value(id + 1);
+                    set_toasts.update(|toasts| toasts.push((id, achievement)));
+                    set_timeout(
+                        move || set_toasts.update(|toasts| toasts.retain(|(i, _)| *i != id)),
+                        TOAST_DURATION,
+                    );
                 }
-            } else {
-                let now = chrono::Local::now().naive_local();
-                add_miss(AsciiChars::from_char(want_char).unwrap(), now);
+                save_profile();
+            }
 
-                if let Some(input) = input_ref.get() {
-                    input.set_value("");
+            let attempts = attempt_count();
+            if attempts == 1 {
+                set_run_started.set(Some(now()));
+            }
+            let current_settings = settings.get_untracked();
+            let lesson = match current_settings.drill_mode {
+                DrillMode::Lesson => current_lesson(&current_settings),
+                _ => None,
+            };
+            let test_length = lesson
+                .as_ref()
+                .map_or(current_settings.test_length, |lesson| lesson.length);
+            let timed_out = lesson
+                .as_ref()
+                .and_then(|lesson| lesson.time_limit_secs)
+                .zip(run_started.get_untracked())
+                .is_some_and(|(limit, started)| now().saturating_sub(started) >= limit * 1000);
+            if (test_length > 0 && attempts >= test_length) || timed_out {
+                let mut lesson_passed = false;
+                if let Some(lesson) = lesson {
+                    let (passed, outcome) = finish_lesson(&lesson);
+                    lesson_passed = passed;
+                    set_lesson_outcome.set(Some(outcome));
+                    set_progress.set(lesson_progress());
                 }
-
-                // Update misses count
-                if let Some(miss) = miss_ref.get() {
-                    let current_misses =
-                        STATS.get().unwrap().lock().unwrap().get_total_miss_count();
-                    let current_misses = format!("Misses: {}", current_misses);
-
-                    miss.set_inner_text(&current_misses);
+                set_test_complete.set(true);
+                set_ghost_result.set(ghost_outcome());
+                let new_records = show_report();
+                if lesson_passed || !new_records.is_empty() {
+                    sound::play(&current_settings, Sound::Chime);
                 }
+                save_recording();
+            } else if attempts % AUTOSAVE_INTERVAL == 0 {
+                save_profile();
+                save_recording();
             }
 
-            restart_timer();
+            // Correction time counts against the target still waiting.
+            if !keep_input {
+                restart_timer();
+            }
+        });
+    };
 
-            //let args = serde_wasm_bindgen::to_value(&GreetArgs { name: &name }).unwrap();
-            // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-            //let new_msg = invoke("greet", args).await.as_string().unwrap();
-            //set_greet_msg.set(new_msg);
+    let check_result = move |ev: SubmitEvent| {
+        ev.prevent_default();
+        // Enter submits the form, which is how Enter gets typed.
+        set_theirs.set("\n".to_string());
+        check_input();
+    };
+
+    let check_key = move |ev: KeyboardEvent| {
+        record(|at_ms| KeyEvent::KeyDown {
+            at_ms,
+            key: ev.key(),
+            code: ev.code(),
+        });
+        // `key` is the same for both, so only `code` tells the keypad's
+        // keys from the main ones.
+        set_numpad_key.set(ev.code().starts_with("Numpad"));
+        match ev.code().as_str() {
+            "ShiftLeft" => set_shift_held.set(Some("key-shift-left")),
+            "ShiftRight" => set_shift_held.set(Some("key-shift-right")),
+            _ if !ev.shift_key() => set_shift_held.set(None),
+            _ => {}
+        }
+        // Tab normally moves focus, so only capture it when it's the target;
+        // the rest of the time it keeps working for keyboard navigation.
+        if ev.key() == "Tab" && the_char.get_untracked() == "\t" {
+            ev.prevent_default();
+            set_theirs.set("\t".to_string());
+            check_input();
+        }
+    };
+
+    let release_key = move |ev: KeyboardEvent| {
+        record(|at_ms| KeyEvent::KeyUp {
+            at_ms,
+            key: ev.key(),
+            code: ev.code(),
         });
     };
 
-    let update_theirs = move |ev| {
+    let update_theirs = move |ev: Event| {
+        // A dead key starts a composition; wait for the composed character.
+        if ev
+            .dyn_ref::<InputEvent>()
+            .is_some_and(|ev| ev.is_composing())
+        {
+            return;
+        }
         let v = event_target_value(&ev);
+        if v.is_empty() {
+            // Backspace took the wrong character away.
+            set_uncorrected.set(false);
+        }
         set_theirs.set(v.clone());
 
         // Auto-submit when a character is typed
         if !v.is_empty() {
             // Trigger the same logic as form submission
-            spawn_local(async move {
-                let want_char = the_char.get_untracked();
-                if want_char.is_empty() {
+            check_input();
+        }
+    };
+
+    let composition_end = move |ev: CompositionEvent| {
+        // Not every browser sends a final input event after composing.
+        let v = event_target_value(&ev);
+        if !v.is_empty() && v != theirs.get_untracked() {
+            set_theirs.set(v);
+            check_input();
+        }
+    };
+
+    // Swaps in the profile `name`, banking the outgoing one first or, with
+    // `delete_current`, deleting it and everything kept for it.
+    let switch_profile = move |name: String, delete_current: bool| {
+        spawn_local(async move {
+            let old_name = PROFILE.get().unwrap().lock().unwrap().name.clone();
+            if delete_current {
+                take_session_events();
+                UNSAVED_BATCHES
+                    .get()
+                    .unwrap()
+                    .lock()
+                    .unwrap()
+                    .retain(|batch| batch.profile != old_name);
+                if let Err(e) = backend::delete_profile(&old_name).await {
+                    set_profile_error.set(Some(format!("Couldn't delete {}: {}", old_name, e)));
                     return;
                 }
+                if let Ok(mut board) = LEADERBOARD.get().unwrap().lock() {
+                    board.remove_profile(&old_name);
+                }
+                set_profile_names.update(|names| names.retain(|other| *other != old_name));
+            } else {
+                update_personal_bests();
+                save_recording();
+                let save = backend::save_profile(&PROFILE.get().unwrap().lock().unwrap());
+                let session = take_session_events();
+                let _ = save.await;
+                save_session_events(&old_name, session).await;
+            }
+            set_profile_error.set(None);
 
-                let theirs = theirs.get_untracked();
-                if theirs.is_empty() {
-                    return;
+            let mut profile = match backend::load_profile(&name).await {
+                Ok(Some(profile)) => profile,
+                _ => Profile::new(&name),
+            };
+            let stats = std::mem::replace(&mut profile.stats, Stats::new());
+            profile.settings.apply();
+            set_settings.set(profile.settings.clone());
+            set_progress.set(profile.lessons.clone());
+            set_achievements.set(profile.achievements.clone());
+            // The old profile's ghost isn't this one's to race.
+            *GHOST.get().unwrap().lock().unwrap() = None;
+            *RECORDING.get().unwrap().lock().unwrap() = new_recording(&profile.settings);
+            if let Ok(mut current) = PROFILE.get().unwrap().lock() {
+                *current = profile;
+            }
+            *HISTORY.get().unwrap().lock().unwrap() = History::default();
+            load_history(&name, stats).await;
+            set_today.set(today_total());
+            match text_source(&settings.get_untracked()) {
+                Some(source) => load_passages(&source, settings.get_untracked().layout).await,
+                None => PASSAGES.get().unwrap().lock().unwrap().clear(),
+            }
+            let _ = backend::set_active_profile(&name).await;
+
+            set_profile_names.update(|names| {
+                if !names.contains(&name) {
+                    names.push(name.clone());
+                    names.sort_unstable();
                 }
+            });
+            set_active_profile.set(name);
+            set_test_complete.set(false);
+            set_lesson_outcome.set(None);
+            set_ghost_result.set(None);
 
-                let their_char = theirs.chars().nth(0).unwrap();
-                let want_char = want_char.chars().nth(0).unwrap();
+            restart_drill();
+            load_ghost();
+            refresh_counters();
+            if let Some(report) = report_ref.get() {
+                report.set_inner_html("");
+            }
+            if let Some(input) = input_ref.get() {
+                let _ = input.focus();
+            }
+            restart_timer();
+        });
+    };
 
-                if their_char == want_char {
-                    let ms = stop_timer();
-                    let now = chrono::Local::now().naive_local();
-                    add_hit(AsciiChars::from_char(want_char).unwrap(), now, ms);
+    spawn_local(async move {
+        if let Ok(user_snippets) = backend::list_user_snippets().await {
+            SNIPPETS.get().unwrap().lock().unwrap().extend(user_snippets);
+        }
+    });
 
-                    next_char();
-                    // Clear DOM input
-                    if let Some(input) = input_ref.get() {
-                        input.set_value("");
-                    }
+    // The practice text source is remembered in settings; reload it.
+    if let Some(source) = text_source(&settings.get_untracked()) {
+        spawn_local(async move {
+            load_passages(&source, settings.get_untracked().layout).await;
+            if is_line_drill(&settings.get_untracked()) {
+                restart_drill();
+            }
+        });
+    }
 
-                    // Update hit count
-                    if let Some(hit) = hit_ref.get() {
-                        let current_hits =
-                            STATS.get().unwrap().lock().unwrap().get_total_hit_count();
-                        let current_hits = format!("Hits: {}", current_hits);
+    spawn_local(async move {
+        let builtin = lessons.get_untracked();
+        match backend::list_user_lessons(&builtin).await {
+            Ok((user_lessons, errors)) => {
+                LESSONS.get().unwrap().lock().unwrap().extend(user_lessons.clone());
+                set_lessons.update(|list| list.extend(user_lessons));
+                set_lesson_errors.set(errors.iter().map(|e| e.to_string()).collect());
+            }
+            Err(e) => set_lesson_errors.set(vec![e]),
+        }
+        // The saved lesson may be one of the user's own.
+        let current = settings.get_untracked();
+        if current.drill_mode == DrillMode::Lesson {
+            if let Some(source) = text_source(&current) {
+                load_passages(&source, current.layout).await;
+            }
+            restart_drill();
+        }
+    });
 
-                        hit.set_inner_text(&current_hits);
+    // Fill the switcher; the active profile itself was loaded before mounting.
+    spawn_local(async move {
+        if let Ok(names) = backend::list_profiles().await {
+            set_profile_names.update(|list| {
+                for name in names {
+                    if !list.contains(&name) {
+                        list.push(name);
                     }
-                } else {
-                    let now = chrono::Local::now().naive_local();
-                    add_miss(AsciiChars::from_char(want_char).unwrap(), now);
+                }
+                list.sort_unstable();
+            });
+        }
+    });
+    Effect::new(move |_| refresh_counters());
+    Effect::new(move |_| {
+        let layout = settings.get().layout;
+        if let Some(keyboard) = svg_keyboard_ref.get() {
+            SvgKeyboard::relabel(&keyboard, layout);
+            SvgKeyboard::mark_fingers(&keyboard, layout);
+        }
+    });
 
-                    if let Some(input) = input_ref.get() {
-                        input.set_value("");
-                    }
+    // Banks the run so far and clears it, so the next one starts from zero.
+    let start_new_run = move || {
+        update_personal_bests();
+        if let Ok(mut profile) = PROFILE.get().unwrap().lock() {
+            profile.stats.reset();
+        }
+        save_profile();
+        keep_ghost();
+        restart_recording();
+        set_test_complete.set(false);
+        set_lesson_outcome.set(None);
+        set_ghost_result.set(None);
+        refresh_counters();
+        if let Some(report) = report_ref.get() {
+            report.set_inner_html("");
+        }
+    };
 
-                    // Update misses count
-                    if let Some(miss) = miss_ref.get() {
-                        let current_misses =
-                            STATS.get().unwrap().lock().unwrap().get_total_miss_count();
-                        let current_misses = format!("Misses: {}", current_misses);
+    let change_settings = move |mut new_settings: Settings| {
+        let old_settings = settings.get_untracked();
+        if new_settings.drill_mode == DrillMode::Lesson && new_settings.lesson.is_none() {
+            new_settings.lesson = current_lesson(&new_settings).map(|lesson| lesson.id);
+        }
+        if new_settings == old_settings {
+            return;
+        }
+        let lesson_changed = new_settings.drill_mode == DrillMode::Lesson
+            && (old_settings.drill_mode != DrillMode::Lesson
+                || new_settings.lesson != old_settings.lesson);
+        // A lesson is judged on a run of its own, and any other new drill
+        // starts one too.
+        let drill_changed = new_settings.char_set != old_settings.char_set
+            || new_settings.layout != old_settings.layout
+            || new_settings.drill_mode != old_settings.drill_mode
+            || new_settings.code_language != old_settings.code_language
+            || new_settings.skip_indent != old_settings.skip_indent
+            || new_settings.look_ahead != old_settings.look_ahead
+            || new_settings.error_policy != old_settings.error_policy
+            || lesson_changed;
+        if drill_changed {
+            start_new_run();
+        }
+        new_settings.apply();
+        set_settings.set(new_settings.clone());
 
-                        miss.set_inner_text(&current_misses);
-                    }
-                }
+        let name = if let Ok(mut profile) = PROFILE.get().unwrap().lock() {
+            profile.settings = new_settings.clone();
+            profile.name.clone()
+        } else {
+            return;
+        };
+        let new_source = text_source(&new_settings);
+        let source_changed = new_source != text_source(&old_settings);
+        // Passages only keep what the layout can type.
+        let layout = new_settings.layout;
+        let layout_changed = layout != old_settings.layout;
+        let ghost_changed = new_settings.ghost != old_settings.ghost;
+        spawn_local(async move {
+            let _ = backend::save_settings(&name, &new_settings).await;
+        });
 
+        if let (true, Some(source)) = (source_changed || layout_changed, new_source) {
+            spawn_local(async move {
+                load_passages(&source, layout).await;
+                restart_drill();
                 restart_timer();
             });
+        } else if source_changed || drill_changed {
+            if source_changed {
+                PASSAGES.get().unwrap().lock().unwrap().clear();
+            }
+            restart_drill();
+            restart_timer();
+        }
+        // A new drill has a different ghost, if any.
+        if drill_changed || source_changed || ghost_changed {
+            load_ghost();
         }
     };
 
-    let update_report = move |_| {
-        if let Some(report) = report_ref.get() {
-            let stats = STATS.get().unwrap().lock().unwrap();
-            let report_content = stats.generate_html_report();
-
-            report.set_inner_html(&report_content);
+    // Reminds the user, once a day, when the reminder hour has come and the
+    // daily goal is still to do.
+    let remind = move || {
+        let settings = settings.get_untracked();
+        let now = chrono::Local::now().naive_local();
+        let due = settings
+            .reminder_hour
+            .is_some_and(|hour| now.hour() >= hour && reminded.get_value() != Some(now.date()));
+        if !due {
+            return;
+        }
+        let Some((done, goal)) = settings.goal_progress(&today_total()) else {
+            return;
+        };
+        if done >= goal {
+            return;
         }
+        reminded.set_value(Some(now.date()));
+        let body = format!(
+            "{} of {} {} practised today.",
+            done,
+            goal,
+            settings.goal_kind.label()
+        );
+        spawn_local(async move {
+            let _ = backend::show_reminder("Daily typing goal", &body).await;
+        });
     };
+    set_interval(remind, REMINDER_CHECK);
 
-    let reset_stats = move |_| {
-        if let Ok(mut stats) = STATS.get().unwrap().lock() {
-            stats.reset();
+    let toggle_keyboard = move |_: Event| {
+        let mut new_settings = settings.get_untracked();
+        new_settings.keyboard_visible = !new_settings.keyboard_visible;
+        change_settings(new_settings);
+    };
+
+    let select_profile = move |ev: Event| {
+        let name = event_target_value(&ev);
+        if name != active_profile.get_untracked() {
+            switch_profile(name, false);
         }
-        next_char();
-        if let Some(hit) = hit_ref.get() {
-            hit.set_inner_text("Hits: 0");
+    };
+
+    let create_profile = move |ev: SubmitEvent| {
+        ev.prevent_default();
+        let name = new_profile_name.get_untracked();
+        if Profile::is_valid_name(&name) && !profile_names.get_untracked().contains(&name) {
+            set_new_profile_name.set(String::new());
+            switch_profile(name, false);
         }
-        if let Some(miss) = miss_ref.get() {
-            miss.set_inner_text("Misses: 0");
+    };
+
+    let delete_profile = move |_| {
+        let name = active_profile.get_untracked();
+        let confirmed = window()
+            .confirm_with_message(&format!(
+                "Delete the profile \"{}\", with its history, recordings and leaderboard entries?",
+                name
+            ))
+            .unwrap_or(false);
+        if !confirmed {
+            return;
         }
-        if let Some(report) = report_ref.get() {
-            report.set_inner_html("");
+        // Another profile takes its place; a fresh default one if it was the last.
+        let next = profile_names
+            .get_untracked()
+            .into_iter()
+            .find(|other| *other != name)
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+        switch_profile(next, true);
+    };
+
+    let select_lesson = move |ev: Event| {
+        let mut new_settings = settings.get_untracked();
+        new_settings.lesson = Some(event_target_value(&ev));
+        change_settings(new_settings);
+    };
+
+    let update_report = move |_| {
+        if !show_report().is_empty() {
+            sound::play(&settings.get_untracked(), Sound::Chime);
         }
+    };
+
+    let reset_stats = move |_| {
+        // The run is over, so bank any records before clearing it.
+        start_new_run();
+        restart_drill();
         if let Some(input) = input_ref.get() {
             let _ = input.focus();
         }
@@ -349,39 +1535,345 @@ pub fn App() -> impl IntoView {
 
     view! {
         <main class="container">
+            <div class="profile-container">
+                <label for="profile-select">"Profile"</label>
+                <select id="profile-select" on:change=select_profile>
+                    <For each=move || profile_names.get()
+                        key=|name| name.clone()
+                        let:name>
+                        <option
+                            value=name.clone()
+                            selected={
+                                let name = name.clone();
+                                move || active_profile.get() == name
+                            }>
+                            {name.clone()}
+                        </option>
+                    </For>
+                </select>
+                <form on:submit=create_profile>
+                    <input
+                        id="new-profile"
+                        placeholder="New profile..."
+                        maxlength="32"
+                        prop:value=move || new_profile_name.get()
+                        on:input=move |ev| set_new_profile_name.set(event_target_value(&ev))
+                    />
+                </form>
+                <button type="button" on:click=delete_profile>"Delete profile"</button>
+                <Show when=move || profile_error.get().is_some()>
+                    <p class="hint" role="alert">{move || profile_error.get().unwrap_or_default()}</p>
+                </Show>
+            </div>
             <div class="toggle-container">
                 <span class="toggle-label">"Keyboard"</span>
                 <label class="toggle-switch">
-                    <input type="checkbox" id="keyboard-toggle" on:change=toggle_keyboard/>
+                    <input
+                        type="checkbox"
+                        id="keyboard-toggle"
+                        aria-label="Keyboard"
+                        prop:checked=move || settings.get().keyboard_visible
+                        on:change=toggle_keyboard/>
                     <span class="toggle-slider"></span>
                 </label>
+                <button
+                    type="button"
+                    aria-pressed=move || settings_open.get().to_string()
+                    on:click=move |_| set_settings_open.update(|open| *open = !*open)>
+                    "Settings"
+                </button>
+                <button
+                    type="button"
+                    aria-pressed=move || replay_open.get().to_string()
+                    on:click=move |_| set_replay_open.update(|open| *open = !*open)>
+                    "Replay"
+                </button>
+                <button
+                    type="button"
+                    aria-pressed=move || history_open.get().to_string()
+                    on:click=move |_| set_history_open.update(|open| *open = !*open)>
+                    "History"
+                </button>
+                <button
+                    type="button"
+                    aria-pressed=move || gallery_open.get().to_string()
+                    on:click=move |_| set_gallery_open.update(|open| *open = !*open)>
+                    "Achievements"
+                </button>
+                <button
+                    type="button"
+                    aria-pressed=move || calendar_open.get().to_string()
+                    on:click=move |_| set_calendar_open.update(|open| *open = !*open)>
+                    "Calendar"
+                </button>
+                <button
+                    type="button"
+                    aria-pressed=move || race_open.get().to_string()
+                    on:click=move |_| set_race_open.update(|open| *open = !*open)>
+                    "Race"
+                </button>
             </div>
+            <Show when=move || settings_open.get()>
+                <SettingsPanel settings on_change=Callback::new(change_settings)/>
+            </Show>
+            <Show when=move || settings.get().drill_mode == DrillMode::Lesson>
+                <div class="lesson-container">
+                    <label for="lesson-select">"Lesson"</label>
+                    <select id="lesson-select" on:change=select_lesson>
+                        {move || {
+                            let progress = progress.get();
+                            let current = current_lesson(&settings.get()).map(|lesson| lesson.id);
+                            lessons
+                                .get()
+                                .into_iter()
+                                .map(|lesson| {
+                                    let unlocked = progress.is_unlocked(&lesson);
+                                    let label = if progress.is_passed(&lesson.id) {
+                                        format!("\u{2713} {}", lesson.title)
+                                    } else if unlocked {
+                                        lesson.title.clone()
+                                    } else {
+                                        format!("{} (locked)", lesson.title)
+                                    };
+                                    view! {
+                                        <option
+                                            value=lesson.id.clone()
+                                            disabled=!unlocked
+                                            selected=current.as_deref() == Some(lesson.id.as_str())>
+                                            {label}
+                                        </option>
+                                    }
+                                })
+                                .collect_view()
+                        }}
+                    </select>
+                    <span class="hint">
+                        { move || current_lesson(&settings.get()).map(|lesson| lesson.goal()).unwrap_or_default() }
+                    </span>
+                </div>
+                <For each=move || lesson_errors.get()
+                    key=|error| error.clone()
+                    let:error>
+                    <p class="hint lesson-error">{error}</p>
+                </For>
+            </Show>
             <h1>"Type the Character"</h1>
-            <p id="want-input">{ move || the_char.get() }</p>
-            <div node_ref=svg_keyboard_ref
-                id="svg_keyboard"
-                inner_html=SvgKeyboard::render()
-                hidden=move || !keyboard_visible.get()></div>
+            <div class="visually-hidden" role="status" aria-live="polite">
+                { move || announcement.get() }
+            </div>
+            // Announced through the live region above instead.
+            <p
+                id="want-input"
+                class:name-prompt=move || settings.get().drill_mode == DrillMode::Names
+                aria-hidden="true">
+                <span class="current-char">
+                    { move || {
+                        let settings = settings.get();
+                        the_char.get().chars().map(|c| prompt_text(&settings, c)).collect::<String>()
+                    }}
+                </span>
+                // Line drills show what's coming in the line itself.
+                <Show when=move || !is_line_drill(&settings.get())>
+                    <span class="upcoming-chars">
+                        { move || {
+                            let settings = settings.get();
+                            let upcoming = upcoming.get().into_iter().map(|c| prompt_text(&settings, c));
+                            match settings.drill_mode {
+                                DrillMode::Names => upcoming.collect::<Vec<_>>().join(" \u{b7} "),
+                                _ => upcoming.collect::<String>(),
+                            }
+                        }}
+                    </span>
+                </Show>
+            </p>
+            // The name drill shows the name in place of the character.
+            <Show when=move || settings.get().drill_mode != DrillMode::Names>
+                <p class="char-name" aria-hidden="true">
+                    { move || {
+                        let settings = settings.get();
+                        the_char
+                            .get()
+                            .chars()
+                            .next()
+                            .and_then(|c| target_char(&settings, c))
+                            .and_then(|target| target.name())
+                            .unwrap_or_default()
+                    }}
+                </p>
+            </Show>
+            <Show when=move || missed.get().is_some() && !is_line_drill(&settings.get())>
+                <p class="hint missed">
+                    "Missed " { move || missed.get().map(glyph).map(String::from).unwrap_or_default() }
+                </p>
+            </Show>
+            <Show when=move || key_hint.get().is_some()>
+                <p class="hint">{ move || key_hint.get().unwrap_or_default() }</p>
+            </Show>
+            <Show when=move || settings.get().finger_guide && finger_hint.get().is_some()>
+                <p class="hint finger-hint">{ move || finger_hint.get().unwrap_or_default() }</p>
+            </Show>
+            <Show when=move || {
+                settings.get().drill_mode == DrillMode::Text && the_char.get().is_empty()
+            }>
+                <p class="hint">"Choose a text file or folder under Settings to practise on it."</p>
+            </Show>
+            <Show when=move || is_line_drill(&settings.get())>
+                <pre id="snippet-line">
+                    <span class="snippet-typed">
+                        { move || {
+                            let view = line_view.get();
+                            view.typed
+                                .chars()
+                                .enumerate()
+                                .map(|(i, typed)| {
+                                    view! {
+                                        <span class:snippet-error=view.errors.contains(&i)>{typed}</span>
+                                    }
+                                })
+                                .collect_view()
+                        }}
+                    </span>
+                    <span class="snippet-current">
+                        { move || line_view.get().current.map(glyph).map(String::from).unwrap_or_default() }
+                    </span>
+                    <span>{ move || line_view.get().remaining }</span>
+                </pre>
+                <pre id="snippet-next">{ move || line_view.get().next_line.unwrap_or_default() }</pre>
+            </Show>
+            <div
+                class="keyboards"
+                class:finger-guide=move || settings.get().finger_guide
+                aria-hidden="true">
+                <div node_ref=svg_keyboard_ref
+                    id="svg_keyboard"
+                    inner_html=SvgKeyboard::render()
+                    hidden=move || !settings.get().keyboard_visible></div>
+                <div
+                    id="svg_numpad"
+                    inner_html=SvgKeyboard::render_numpad()
+                    hidden=move || {
+                        let settings = settings.get();
+                        !settings.keyboard_visible
+                            || !(settings.numpad_visible || settings.drill_mode == DrillMode::Numpad)
+                    }></div>
+            </div>
             <form class="row" on:submit=check_result>
                 <input
                     node_ref=input_ref
                     id="greet-input"
                     placeholder="Type the character..."
                     maxlength="1"
+                    class:uncorrected=move || uncorrected.get()
+                    disabled=move || test_complete.get()
+                    on:keydown=check_key
+                    on:keyup=release_key
                     on:input=update_theirs
+                    on:compositionend=composition_end
                 />
+                // A mark as well as a colour, so it reads without colour vision.
+                <span
+                    class="feedback"
+                    class:feedback-hit=move || last_result.get() == Some(true)
+                    class:feedback-miss=move || last_result.get() == Some(false)
+                    aria-hidden="true">
+                    { move || match last_result.get() {
+                        Some(true) => "\u{2713}",
+                        Some(false) => "\u{2717}",
+                        None => "",
+                    }}
+                </span>
             </form>
+            <Show when=move || uncorrected.get()>
+                <p class="hint">"Press Backspace to delete the wrong character."</p>
+            </Show>
             <br/>
             <div>
               <button type="button" on:click=reset_stats>"Reset"</button>
               <button type="button" on:click=update_report>"Update Report"</button>
             </div>
+            <Show when=move || test_complete.get()>
+                <p id="test-complete" role="alert">"Test complete! Press Reset to start another."</p>
+                <p class="lesson-outcome">{ move || lesson_outcome.get().unwrap_or_default() }</p>
+                <p class="ghost-outcome">{ move || ghost_result.get().unwrap_or_default() }</p>
+            </Show>
+            <Show when=move || ghost_standing.get().is_some()>
+                <ul class="race-players ghost-race">
+                    { move || {
+                        let Some(standing) = ghost_standing.get() else {
+                            return ().into_any();
+                        };
+                        let ghost_name = format!("{} ghost", standing.kind.label());
+                        let lead = standing.you as i64 - standing.ghost as i64;
+                        let status = match lead {
+                            0 => "Level".to_string(),
+                            lead if lead > 0 => format!("Ahead by {}", lead),
+                            lead => format!("Behind by {}", -lead),
+                        };
+                        view! {
+                            <li class="race-me">
+                                <span class="race-name">"You"</span>
+                                <progress
+                                    max=standing.length
+                                    value=standing.you
+                                    aria-label="You"></progress>
+                                <span class="race-status">{status}</span>
+                            </li>
+                            <li>
+                                <span class="race-name">{ghost_name.clone()}</span>
+                                <progress
+                                    max=standing.length
+                                    value=standing.ghost
+                                    aria-label=ghost_name></progress>
+                                <span class="race-status">
+                                    {format!("{} / {}", standing.ghost, standing.length)}
+                                </span>
+                            </li>
+                        }
+                        .into_any()
+                    }}
+                </ul>
+            </Show>
             <p id="hits" node_ref=hit_ref></p>
             <p id="misses" node_ref=miss_ref></p>
+            <Show when=move || { settings.get().daily_goal > 0 }>
+                <p class="hint goal-progress">
+                    { move || {
+                        let settings = settings.get();
+                        let (done, goal) = settings.goal_progress(&today.get()).unwrap_or_default();
+                        let met = if done >= goal { " \u{2713} Goal met!" } else { "" };
+                        format!("Today: {} / {} {}{}", done, goal, settings.goal_kind.label(), met)
+                    }}
+                </p>
+            </Show>
             <div id="report"
                 node_ref=report_ref></div>
+            <Show when=move || gallery_open.get()>
+                <AchievementGallery achievements/>
+            </Show>
+            <Show when=move || calendar_open.get()>
+                <PracticeCalendar
+                    days=HISTORY.get().unwrap().lock().unwrap().day_totals()
+                    today=chrono::Local::now().date_naive()
+                    settings/>
+            </Show>
+            <Show when=move || race_open.get()>
+                <RacePanel profile=active_profile settings/>
+            </Show>
+            <Show when=move || history_open.get()>
+                <HistoryPanel profile=active_profile/>
+            </Show>
+            <Show when=move || replay_open.get()>
+                <ReplayViewer profile=active_profile/>
+            </Show>
 
+            <div class="toasts">
+                <For each=move || toasts.get() key=|(id, _)| *id let:toast>
+                    <div class="toast" role="status">
+                        <strong>"Achievement unlocked: " {toast.1.label()}</strong>
+                        <p>{toast.1.description()}</p>
+                    </div>
+                </For>
+            </div>
         </main>
     }
 }
-// <button type="submit">"Greet"</button>
@@ -6,6 +6,9 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
 
 pub enum AsciiChars {
+    Tab = 9,
+    Enter = 10,
+    Space = 32,
     Exclamation = 33,
     DoubleQuote = 34,
     Hash = 35,
@@ -104,12 +107,15 @@ pub enum AsciiChars {
 
 impl AsciiChars {
     pub fn as_char(&self) -> char {
-        unsafe { char::from_u32_unchecked(self.as_u8() as u32) }
+        char::from(self.as_u8())
     }
 
     pub const fn from_char(c: char) -> Option<Self> {
+        if !c.is_ascii() {
+            return None;
+        }
         match c as u8 {
-            33..=126 => Self::from_u8(c as u8),
+            b'\t' | b'\n' | b' ' | 33..=126 => Self::from_u8(c as u8),
             _ => None,
         }
     }
@@ -0,0 +1,455 @@
+use crate::history::{HistoryEvent, LogFrame};
+use crate::lessons::{parse_lessons, Lesson, LessonError};
+use crate::personal_best::{Leaderboard, LeaderboardEntry};
+use crate::profile::{Profile, DEFAULT_PROFILE};
+use crate::recording::{KeyEvent, Recording, RecordingHeader};
+use crate::settings::Settings;
+use crate::snippets::{Language, Snippet};
+use crate::stats::HitType;
+use crate::target_char::TargetChar;
+use chrono::NaiveDateTime;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::future::Future;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+struct NoArgs {}
+
+#[derive(Serialize)]
+struct PickArgs {
+    directory: bool,
+}
+
+#[derive(Serialize)]
+struct PathArgs<'a> {
+    path: &'a str,
+}
+
+#[derive(Deserialize)]
+pub struct PracticeText {
+    pub path: String,
+    pub text: String,
+}
+
+#[derive(Deserialize)]
+struct UserSnippet {
+    name: String,
+    extension: String,
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct UserLesson {
+    name: String,
+    text: String,
+}
+
+#[derive(Serialize)]
+struct EntryArgs<'a> {
+    entry: &'a LeaderboardEntry,
+}
+
+#[derive(Serialize)]
+struct NameArgs<'a> {
+    name: &'a str,
+}
+
+#[derive(Serialize)]
+struct ProfileArgs<'a> {
+    name: &'a str,
+    profile: &'a Profile,
+}
+
+#[derive(Serialize)]
+struct RecordingsArgs<'a> {
+    profile: &'a str,
+}
+
+#[derive(Serialize)]
+struct RecordingIdArgs<'a> {
+    profile: &'a str,
+    id: &'a str,
+}
+
+#[derive(Serialize)]
+struct RecordingArgs<'a> {
+    profile: &'a str,
+    id: &'a str,
+    header: Option<RecordingHeader<'a>>,
+    events: &'a [KeyEvent],
+}
+
+#[derive(Serialize)]
+struct HistoryArgs<'a> {
+    profile: &'a str,
+}
+
+#[derive(Serialize)]
+struct AppendHistoryArgs<'a> {
+    profile: &'a str,
+    header: &'a [u8],
+    frame: &'a [u8],
+}
+
+#[derive(Serialize)]
+struct TruncateHistoryArgs<'a> {
+    profile: &'a str,
+    len: usize,
+}
+
+/// A run as the history database knows it.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionKey {
+    pub started: NaiveDateTime,
+    pub mode: String,
+}
+
+#[derive(Serialize)]
+struct EventRow {
+    char_id: TargetChar,
+    at: NaiveDateTime,
+    hit: bool,
+    reaction_ms: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct SessionEventsArgs<'a> {
+    profile: &'a str,
+    session: &'a SessionKey,
+    offset: u64,
+    events: Vec<EventRow>,
+}
+
+#[derive(Serialize)]
+struct LogFrameRows {
+    offset: u64,
+    events: Vec<EventRow>,
+}
+
+#[derive(Serialize)]
+struct IndexFramesArgs<'a> {
+    profile: &'a str,
+    frames: Vec<LogFrameRows>,
+}
+
+#[derive(Serialize)]
+struct AggregateArgs<'a> {
+    profile: &'a str,
+    since: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct SessionAggregateArgs<'a> {
+    profile: &'a str,
+    limit: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CharAggregate {
+    pub char_id: TargetChar,
+    pub hits: u64,
+    pub misses: u64,
+    pub avg_ms: Option<f64>,
+    pub min_ms: Option<u64>,
+    pub max_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DayAggregate {
+    pub day: String,
+    pub hits: u64,
+    pub misses: u64,
+    pub avg_ms: Option<f64>,
+    /// Time between attempts, leaving out breaks
+    pub practice_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SessionAggregate {
+    pub started: NaiveDateTime,
+    pub ended: NaiveDateTime,
+    pub mode: String,
+    pub hits: u64,
+    pub misses: u64,
+    pub avg_ms: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct ReminderArgs<'a> {
+    title: &'a str,
+    body: &'a str,
+}
+
+#[derive(Serialize)]
+struct RaceServerArgs {
+    port: Option<u16>,
+}
+
+/// Where a hosted race server can be joined.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RaceServer {
+    pub port: u16,
+    /// WebSocket URLs, on this machine first and then on the LAN
+    pub urls: Vec<String>,
+}
+
+fn to_args<A: Serialize>(args: &A) -> Result<JsValue, String> {
+    // Tauri JSON-encodes the arguments, so maps must become plain objects.
+    args.serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Serialize)]
+struct SettingsArgs<'a> {
+    name: &'a str,
+    settings: &'a Settings,
+}
+
+async fn call<A: Serialize, R: DeserializeOwned>(cmd: &str, args: &A) -> Result<R, String> {
+    call_with(cmd, to_args(args)?).await
+}
+
+async fn call_with<R: DeserializeOwned>(cmd: &str, args: JsValue) -> Result<R, String> {
+    // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
+    let value = invoke(cmd, args)
+        .await
+        .map_err(|e| e.as_string().unwrap_or_else(|| format!("{:?}", e)))?;
+    serde_wasm_bindgen::from_value(value).map_err(|e| e.to_string())
+}
+
+pub async fn load_leaderboard() -> Result<Leaderboard, String> {
+    call("load_leaderboard", &NoArgs {}).await
+}
+
+pub async fn submit_leaderboard_entry(entry: &LeaderboardEntry) -> Result<(), String> {
+    call("submit_leaderboard_entry", &EntryArgs { entry }).await
+}
+
+pub async fn list_profiles() -> Result<Vec<String>, String> {
+    call("list_profiles", &NoArgs {}).await
+}
+
+pub async fn load_profile(name: &str) -> Result<Option<Profile>, String> {
+    call("load_profile", &NameArgs { name }).await
+}
+
+pub fn save_profile(profile: &Profile) -> impl Future<Output = Result<(), String>> {
+    // Serialised up front so callers can release the profile lock before awaiting.
+    let args = to_args(&ProfileArgs {
+        name: &profile.name,
+        profile,
+    });
+    async move { call_with("save_profile", args?).await }
+}
+
+pub async fn get_active_profile() -> Result<Option<String>, String> {
+    call("get_active_profile", &NoArgs {}).await
+}
+
+pub async fn set_active_profile(name: &str) -> Result<(), String> {
+    call("set_active_profile", &NameArgs { name }).await
+}
+
+/// Deletes a profile along with its history, recordings and leaderboard entries.
+pub async fn delete_profile(name: &str) -> Result<(), String> {
+    call("delete_profile", &NameArgs { name }).await
+}
+
+pub async fn save_settings(name: &str, settings: &Settings) -> Result<(), String> {
+    call("save_settings", &SettingsArgs { name, settings }).await
+}
+
+pub async fn load_startup_profile() -> Profile {
+    // Falls back to a fresh default profile when running outside Tauri.
+    let name = match get_active_profile().await {
+        Ok(Some(name)) => name,
+        _ => DEFAULT_PROFILE.to_string(),
+    };
+    match load_profile(&name).await {
+        Ok(Some(profile)) => profile,
+        _ => Profile::new(&name),
+    }
+}
+
+pub async fn list_recordings(profile: &str) -> Result<Vec<String>, String> {
+    call("list_recordings", &RecordingsArgs { profile }).await
+}
+
+pub async fn load_recording(profile: &str, id: &str) -> Result<Option<Recording>, String> {
+    call("load_recording", &RecordingIdArgs { profile, id }).await
+}
+
+/// Saves what's new in a recording since it was last saved. Like
+/// `save_profile`, serialised before the caller lets go of the lock.
+pub fn save_recording(
+    profile: &str,
+    recording: &mut Recording,
+) -> impl Future<Output = Result<(), String>> {
+    let id = recording.id();
+    let (header, events) = recording.take_unsaved();
+    let args = (header.is_some() || !events.is_empty()).then(|| {
+        to_args(&RecordingArgs {
+            profile,
+            id: &id,
+            header,
+            events,
+        })
+    });
+    async move {
+        match args {
+            Some(args) => call_with("save_recording", args?).await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// The raw history log; it comes back as an `ArrayBuffer`, not JSON.
+pub async fn load_history(profile: &str) -> Result<Vec<u8>, String> {
+    let value = invoke("load_history", to_args(&HistoryArgs { profile })?)
+        .await
+        .map_err(|e| e.as_string().unwrap_or_else(|| format!("{:?}", e)))?;
+    Ok(js_sys::Uint8Array::new(&value).to_vec())
+}
+
+/// Appends a frame to the history log, starting the log with `header` if it
+/// is new, and returns where the frame starts.
+pub async fn append_history(profile: &str, header: &[u8], frame: &[u8]) -> Result<u64, String> {
+    call(
+        "append_history",
+        &AppendHistoryArgs {
+            profile,
+            header,
+            frame,
+        },
+    )
+    .await
+}
+
+pub async fn truncate_history(profile: &str, len: usize) -> Result<(), String> {
+    call("truncate_history", &TruncateHistoryArgs { profile, len }).await
+}
+
+fn event_rows(events: &[HistoryEvent]) -> Vec<EventRow> {
+    events
+        .iter()
+        .map(|event| EventRow {
+            char_id: event.char_id,
+            at: event.timestamp(),
+            hit: matches!(event.result, HitType::Hit(..)),
+            reaction_ms: match event.result {
+                HitType::Hit(_, reaction_time) => Some(reaction_time),
+                HitType::Miss(_) => None,
+            },
+        })
+        .collect()
+}
+
+/// Adds a run's events to the database, once they are in the history log
+/// at `offset`. Sending the same frame again adds nothing.
+pub async fn add_session_events(
+    profile: &str,
+    session: &SessionKey,
+    offset: u64,
+    events: &[HistoryEvent],
+) -> Result<(), String> {
+    call(
+        "add_session_events",
+        &SessionEventsArgs {
+            profile,
+            session,
+            offset,
+            events: event_rows(events),
+        },
+    )
+    .await
+}
+
+/// Where each history log frame the database has starts.
+pub async fn indexed_frames(profile: &str) -> Result<Vec<u64>, String> {
+    call("indexed_frames", &HistoryArgs { profile }).await
+}
+
+/// Adds log frames the database is missing, returning how many events were
+/// new to it.
+pub async fn index_frames(profile: &str, frames: &[LogFrame]) -> Result<usize, String> {
+    let frames = frames
+        .iter()
+        .map(|frame| LogFrameRows {
+            offset: frame.offset,
+            events: event_rows(&frame.events),
+        })
+        .collect();
+    call("index_frames", &IndexFramesArgs { profile, frames }).await
+}
+
+/// Per-character totals, from `since` ("YYYY-MM-DD") or all time.
+pub async fn char_aggregates(profile: &str, since: Option<&str>) -> Result<Vec<CharAggregate>, String> {
+    call("char_aggregates", &AggregateArgs { profile, since }).await
+}
+
+/// Per-day totals, newest first.
+pub async fn daily_aggregates(profile: &str, since: Option<&str>) -> Result<Vec<DayAggregate>, String> {
+    call("daily_aggregates", &AggregateArgs { profile, since }).await
+}
+
+/// The latest `limit` sessions, newest first.
+pub async fn session_aggregates(profile: &str, limit: u32) -> Result<Vec<SessionAggregate>, String> {
+    call("session_aggregates", &SessionAggregateArgs { profile, limit }).await
+}
+
+/// Shows a desktop notification.
+pub async fn show_reminder(title: &str, body: &str) -> Result<(), String> {
+    call("show_reminder", &ReminderArgs { title, body }).await
+}
+
+/// Starts hosting races on `port`, or the default port.
+pub async fn start_race_server(port: Option<u16>) -> Result<RaceServer, String> {
+    call("start_race_server", &RaceServerArgs { port }).await
+}
+
+pub async fn stop_race_server() -> Result<(), String> {
+    call("stop_race_server", &NoArgs {}).await
+}
+
+pub async fn list_user_snippets() -> Result<Vec<Snippet>, String> {
+    let snippets: Vec<UserSnippet> = call("list_user_snippets", &NoArgs {}).await?;
+    Ok(snippets
+        .into_iter()
+        .filter_map(|s| {
+            Some(Snippet {
+                language: Language::from_extension(&s.extension)?,
+                title: s.name,
+                code: s.code,
+            })
+        })
+        .collect())
+}
+
+/// Loads the user's lesson files, parsed against the lessons already known
+/// (so ids stay unique); files that fail to parse come back as errors.
+pub async fn list_user_lessons(existing: &[Lesson]) -> Result<(Vec<Lesson>, Vec<LessonError>), String> {
+    let files: Vec<UserLesson> = call("list_user_lessons", &NoArgs {}).await?;
+    Ok(parse_lessons(
+        files.iter().map(|f| (f.name.as_str(), f.text.as_str())),
+        existing,
+    ))
+}
+
+pub async fn pick_practice_source(directory: bool) -> Result<Option<String>, String> {
+    call("pick_practice_source", &PickArgs { directory }).await
+}
+
+pub async fn load_practice_text(path: &str) -> Result<PracticeText, String> {
+    call("load_practice_text", &PathArgs { path }).await
+}
+
+/// Reads a lesson's `text_source`, a path inside the lessons folder.
+pub async fn load_lesson_text(path: &str) -> Result<PracticeText, String> {
+    call("load_lesson_text", &PathArgs { path }).await
+}
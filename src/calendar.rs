@@ -0,0 +1,143 @@
+use crate::history::{day_streaks, DayTotal};
+use crate::settings::Settings;
+use chrono::{Datelike, Days, NaiveDate};
+use leptos::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+// How many weeks the heatmap covers, this one included.
+const WEEKS: u64 = 53;
+// Shades above "no practice", the darkest meaning the goal was met.
+const LEVELS: u64 = 4;
+
+fn plural(count: usize, unit: &str) -> String {
+    format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" })
+}
+
+// Against the daily goal when there is one, otherwise against the busiest day.
+fn level(total: &DayTotal, settings: &Settings, busiest: u64) -> u64 {
+    if total.characters == 0 {
+        return 0;
+    }
+    let (done, goal) = settings
+        .goal_progress(total)
+        .unwrap_or((total.characters as u64, busiest));
+    1 + (done * (LEVELS - 1) / goal.max(1)).min(LEVELS - 1)
+}
+
+fn describe(day: NaiveDate, total: &DayTotal) -> String {
+    if total.characters == 0 {
+        return format!("{}: no practice", day.format("%a %Y-%m-%d"));
+    }
+    format!(
+        "{}: {}, {} min",
+        day.format("%a %Y-%m-%d"),
+        plural(total.characters, "character"),
+        total.practice_ms / 60_000
+    )
+}
+
+/// A year of practice days as a heatmap, with the streaks they make.
+#[component]
+pub fn PracticeCalendar(
+    days: BTreeMap<NaiveDate, DayTotal>,
+    today: NaiveDate,
+    settings: ReadSignal<Settings>,
+) -> impl IntoView {
+    let practice_days: BTreeSet<NaiveDate> = days.keys().copied().collect();
+    let (current, longest) = day_streaks(&practice_days, today);
+    let busiest = days
+        .values()
+        .map(|total| total.characters as u64)
+        .max()
+        .unwrap_or_default();
+    // Columns are weeks starting on Monday, so start on one.
+    let start = today
+        - Days::new(today.weekday().num_days_from_monday() as u64 + (WEEKS - 1) * 7);
+    let cells = start
+        .iter_days()
+        .take_while(|day| *day <= today)
+        .map(|day| {
+            let total = days.get(&day).copied().unwrap_or_default();
+            let description = describe(day, &total);
+            view! {
+                <div
+                    class=move || {
+                        format!("calendar-day heat-{}", level(&total, &settings.get(), busiest))
+                    }
+                    role="img"
+                    aria-label=description.clone()
+                    title=description></div>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <div id="practice-calendar">
+            <p>
+                "Current streak: " {plural(current, "day")}
+                " \u{b7} Longest: " {plural(longest, "day")}
+                " \u{b7} Practised on " {plural(practice_days.len(), "day")}
+            </p>
+            <div class="calendar-grid">{cells}</div>
+            <p class="calendar-legend hint" aria-hidden="true">
+                "Less "
+                {(0..=LEVELS)
+                    .map(|level| view! { <span class=format!("calendar-day heat-{}", level)></span> })
+                    .collect_view()}
+                {move || {
+                    if settings.get().daily_goal > 0 { " Goal met" } else { " More" }
+                }}
+            </p>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::GoalKind;
+
+    fn total(characters: usize, minutes: u64) -> DayTotal {
+        DayTotal {
+            characters,
+            practice_ms: minutes * 60_000 + 59_999,
+        }
+    }
+
+    fn goal(daily_goal: u32, goal_kind: GoalKind) -> Settings {
+        Settings {
+            daily_goal,
+            goal_kind,
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn goal_progress_counts_in_the_goal_kind() {
+        let today = total(300, 9);
+        assert_eq!(Settings::default().goal_progress(&today), None);
+        let characters = goal(500, GoalKind::Characters);
+        assert_eq!(characters.goal_progress(&today), Some((300, 500)));
+        // Only whole minutes count.
+        let minutes = goal(10, GoalKind::Minutes);
+        assert_eq!(minutes.goal_progress(&today), Some((9, 10)));
+    }
+
+    #[test]
+    fn a_met_goal_is_the_darkest_shade() {
+        let settings = goal(10, GoalKind::Minutes);
+        assert_eq!(level(&total(0, 0), &settings, 0), 0);
+        assert_eq!(level(&total(50, 0), &settings, 0), 1);
+        assert_eq!(level(&total(50, 5), &settings, 0), 2);
+        assert_eq!(level(&total(50, 10), &settings, 0), LEVELS);
+        assert_eq!(level(&total(50, 25), &settings, 0), LEVELS);
+    }
+
+    #[test]
+    fn without_a_goal_days_are_shaded_against_the_busiest() {
+        let settings = Settings::default();
+        assert_eq!(level(&total(1, 0), &settings, 900), 1);
+        assert_eq!(level(&total(450, 0), &settings, 900), 2);
+        assert_eq!(level(&total(900, 0), &settings, 900), LEVELS);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::layout::Layout;
+use crate::target_char::TargetChar;
+
+/// The name of a character that isn't a letter or digit, as a screen reader
+/// should say it.
+pub fn symbol_name(c: char) -> Option<&'static str> {
+    let name = match c {
+        ' ' => "space",
+        '\n' => "enter",
+        '\t' => "tab",
+        '!' => "exclamation mark",
+        '"' => "double quote",
+        '#' => "hash",
+        '$' => "dollar",
+        '%' => "percent",
+        '&' => "ampersand",
+        '\'' => "apostrophe",
+        '(' => "left parenthesis",
+        ')' => "right parenthesis",
+        '*' => "asterisk",
+        '+' => "plus",
+        ',' => "comma",
+        '-' => "minus",
+        '.' => "full stop",
+        '/' => "slash",
+        ':' => "colon",
+        ';' => "semicolon",
+        '<' => "less than",
+        '=' => "equals",
+        '>' => "greater than",
+        '?' => "question mark",
+        '@' => "at sign",
+        '[' => "left bracket",
+        '\\' => "backslash",
+        ']' => "right bracket",
+        '^' => "caret",
+        '_' => "underscore",
+        '`' => "backtick",
+        '{' => "left brace",
+        '|' => "vertical bar",
+        '}' => "right brace",
+        '~' => "tilde",
+        // Symbols the international layouts add
+        '¡' => "inverted exclamation mark",
+        '¿' => "inverted question mark",
+        '£' => "pound sign",
+        '€' => "euro sign",
+        '¤' => "currency sign",
+        '§' => "section sign",
+        '°' => "degree sign",
+        '¨' => "diaeresis",
+        '´' => "acute accent",
+        '¬' => "not sign",
+        '·' => "middle dot",
+        '²' => "superscript two",
+        '³' => "superscript three",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// What to call a character out loud: symbols by name, and capitals marked
+/// so they aren't heard as lowercase.
+pub fn spoken_name(c: char) -> String {
+    match symbol_name(c) {
+        Some(name) => name.to_string(),
+        None if c.is_uppercase() => format!("capital {}", c),
+        None => c.to_string(),
+    }
+}
+
+/// The symbols `layout` can type that have a name to learn, for the name drill.
+pub fn named_symbols(layout: Layout) -> Vec<TargetChar> {
+    layout
+        .chars()
+        .into_iter()
+        .filter(|target| {
+            let c = target.as_char();
+            !c.is_whitespace() && symbol_name(c).is_some()
+        })
+        .collect()
+}
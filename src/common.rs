@@ -5,14 +5,91 @@ extern "C" {
     #[wasm_bindgen(js_namespace = Math)]
     fn random() -> f64;
 }
-pub fn random_byte(min_val: u8, max_val: u8) -> u8 {
+pub fn random_index(len: usize) -> usize {
     // I can't get the `rand` crate to work with wasm, so rolling my own.
-    let range = (max_val - min_val + 1) as f64;
-    let random_val = (random() * range).floor() as u8;
-    min_val + random_val
+    ((random() * len as f64).floor() as usize).min(len.saturating_sub(1))
+}
+
+/// A fresh seed for `SeededRng`. Kept under 2^53 so it survives being a
+/// JavaScript number.
+pub fn random_seed() -> u64 {
+    (random() * (1u64 << 53) as f64) as u64
+}
+
+/// SplitMix64: the same seed draws the same numbers, so a drill can be
+/// played again character for character.
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn index(&mut self, len: usize) -> usize {
+        match len {
+            0 => 0,
+            len => (self.next_u64() % len as u64) as usize,
+        }
+    }
 }
 
 pub fn now() -> u64 {
     // Get the current time in milliseconds since the Unix epoch
     js_sys::Date::now() as u64
 }
+
+/// CRC-32 (IEEE), as used by zip and PNG.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (!(crc & 1)).wrapping_add(1));
+        }
+    }
+    !crc
+}
+
+pub fn glyph(c: char) -> char {
+    // Whitespace targets need something visible in the prompt.
+    match c {
+        ' ' => '\u{2423}',
+        '\n' => '\u{23CE}',
+        '\t' => '\u{21E5}',
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draws(seed: u64, len: usize) -> Vec<usize> {
+        let mut rng = SeededRng::new(seed);
+        (0..200).map(|_| rng.index(len)).collect()
+    }
+
+    #[test]
+    fn the_same_seed_draws_the_same_drill() {
+        assert_eq!(draws(42, 26), draws(42, 26));
+        assert_ne!(draws(42, 26), draws(43, 26));
+    }
+
+    #[test]
+    fn draws_stay_in_range() {
+        assert!(draws(7, 26).iter().all(|&index| index < 26));
+        assert!(draws(7, 1).iter().all(|&index| index == 0));
+        assert!(draws(7, 0).iter().all(|&index| index == 0));
+    }
+}
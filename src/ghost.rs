@@ -0,0 +1,190 @@
+use crate::recording::{KeyEvent, Recording};
+use crate::settings::GhostKind;
+
+/// When a run's clock starts: at its first keystroke, so time spent reading
+/// the first target doesn't count.
+pub fn run_start_ms(recording: &Recording) -> Option<u64> {
+    recording
+        .events
+        .iter()
+        .find(|event| matches!(event, KeyEvent::Typed { .. }))
+        .map(KeyEvent::at_ms)
+}
+
+/// When each target was passed, in milliseconds on the run's clock: entry
+/// `n` is when the run was `n + 1` characters in.
+pub fn passed_times(recording: &Recording) -> Vec<u64> {
+    let Some(start) = run_start_ms(recording) else {
+        return Vec::new();
+    };
+    recording
+        .events
+        .iter()
+        .skip_while(|event| !matches!(event, KeyEvent::Typed { .. }))
+        .filter(|event| matches!(event, KeyEvent::Target { .. }))
+        .map(|event| event.at_ms().saturating_sub(start))
+        .collect()
+}
+
+/// A past run to race against, replayed from its recording.
+#[derive(Debug, Clone)]
+pub struct Ghost {
+    pub kind: GhostKind,
+    /// Personal best key of the drill, which the race has to share
+    pub mode: String,
+    /// Draws the ghost's characters again for the race
+    pub seed: u64,
+    passed_ms: Vec<u64>,
+}
+
+/// How a run and its ghost stand, in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Standing {
+    pub kind: GhostKind,
+    pub you: usize,
+    pub ghost: usize,
+    /// How far the ghost got in all
+    pub length: usize,
+}
+
+impl Ghost {
+    /// Needs a seeded recording with something typed in it.
+    pub fn from_recording(recording: &Recording, kind: GhostKind) -> Option<Ghost> {
+        let passed_ms = passed_times(recording);
+        if passed_ms.is_empty() {
+            return None;
+        }
+        Some(Ghost {
+            kind,
+            mode: recording.mode.clone(),
+            seed: recording.seed?,
+            passed_ms,
+        })
+    }
+
+    /// Whether `recording` is on the ghost's characters.
+    pub fn races(&self, recording: &Recording) -> bool {
+        recording.seed == Some(self.seed) && recording.mode == self.mode
+    }
+
+    /// Characters the ghost had passed `elapsed_ms` into its run.
+    pub fn position_at(&self, elapsed_ms: u64) -> usize {
+        self.passed_ms.partition_point(|&at| at <= elapsed_ms)
+    }
+
+    /// Where `recording` stands against the ghost at `now_ms`, or when it
+    /// last passed a target once it's `finished`.
+    pub fn standing(&self, recording: &Recording, now_ms: u64, finished: bool) -> Standing {
+        let passed = passed_times(recording);
+        let elapsed = match run_start_ms(recording) {
+            None => 0,
+            Some(_) if finished => passed.last().copied().unwrap_or_default(),
+            Some(start) => recording.elapsed_ms(now_ms).saturating_sub(start),
+        };
+        Standing {
+            kind: self.kind,
+            you: passed.len(),
+            ghost: if passed.is_empty() { 0 } else { self.position_at(elapsed) },
+            length: self.passed_ms.len(),
+        }
+    }
+
+    /// How many milliseconds ahead of the ghost `recording` reached the
+    /// furthest character both got to; negative when behind.
+    pub fn lead_ms(&self, recording: &Recording) -> Option<i64> {
+        let passed = passed_times(recording);
+        let at = passed.len().min(self.passed_ms.len()).checked_sub(1)?;
+        Some(self.passed_ms[at] as i64 - passed[at] as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Layout;
+    use crate::target_char::TargetChar;
+    use crate::test_fixtures::at;
+
+    const SEED: u64 = 42;
+
+    // A run with a hit at each of `typed_ms`, each bringing up the next
+    // target; the first target is up from the start.
+    fn run(seed: u64, typed_ms: &[u64]) -> Recording {
+        let target = TargetChar::new('a').unwrap();
+        let mut recording =
+            Recording::new("letters-qwerty".to_string(), Layout::Qwerty, seed, at(0), 0);
+        recording.push(KeyEvent::Target { at_ms: 0, target });
+        for &at_ms in typed_ms {
+            recording.push(KeyEvent::Typed {
+                at_ms,
+                typed: 'a',
+                target,
+                hit: true,
+            });
+            recording.push(KeyEvent::Target { at_ms, target });
+        }
+        recording
+    }
+
+    fn ghost() -> Ghost {
+        Ghost::from_recording(&run(SEED, &[1_000, 1_100, 1_200, 1_300]), GhostKind::Best).unwrap()
+    }
+
+    #[test]
+    fn the_clock_starts_at_the_first_keystroke() {
+        let recording = run(SEED, &[1_000, 1_100, 1_250]);
+        assert_eq!(run_start_ms(&recording), Some(1_000));
+        assert_eq!(passed_times(&recording), [0, 100, 250]);
+        assert!(passed_times(&run(SEED, &[])).is_empty());
+    }
+
+    #[test]
+    fn a_ghost_needs_a_seeded_run_with_typing() {
+        assert!(Ghost::from_recording(&run(SEED, &[]), GhostKind::Last).is_none());
+        let mut unseeded = run(SEED, &[1_000]);
+        unseeded.seed = None;
+        assert!(Ghost::from_recording(&unseeded, GhostKind::Last).is_none());
+    }
+
+    #[test]
+    fn a_ghost_only_races_its_own_drill() {
+        let ghost = ghost();
+        assert!(ghost.races(&run(SEED, &[])));
+        assert!(!ghost.races(&run(SEED + 1, &[])));
+        let mut other_mode = run(SEED, &[]);
+        other_mode.mode = "digits-qwerty".to_string();
+        assert!(!ghost.races(&other_mode));
+    }
+
+    #[test]
+    fn standing_compares_characters_at_the_same_time() {
+        let ghost = ghost();
+        assert_eq!(ghost.position_at(0), 1);
+        assert_eq!(ghost.position_at(199), 2);
+        assert_eq!(ghost.position_at(5_000), 4);
+
+        // Half a second in, having started late, with three characters
+        // done to the ghost's four.
+        let recording = run(SEED, &[2_000, 2_050, 2_100]);
+        let standing = ghost.standing(&recording, 2_500, false);
+        assert_eq!((standing.you, standing.ghost, standing.length), (3, 4, 4));
+
+        // Once finished, the ghost is stopped at the run's last character.
+        let standing = ghost.standing(&recording, 9_000, true);
+        assert_eq!((standing.you, standing.ghost), (3, 2));
+
+        let waiting = ghost.standing(&run(SEED, &[]), 9_000, false);
+        assert_eq!((waiting.you, waiting.ghost), (0, 0));
+    }
+
+    #[test]
+    fn lead_is_the_gap_at_the_furthest_shared_character() {
+        let ghost = ghost();
+        // Third character at 100 ms, where the ghost took 200 ms.
+        assert_eq!(ghost.lead_ms(&run(SEED, &[0, 50, 100])), Some(100));
+        // Behind: fourth character at 450 ms to the ghost's 300 ms.
+        let behind = run(SEED, &[0, 150, 300, 450, 600]);
+        assert_eq!(ghost.lead_ms(&behind), Some(-150));
+        assert_eq!(ghost.lead_ms(&run(SEED, &[])), None);
+    }
+}
@@ -0,0 +1,573 @@
+use crate::ascii_chars::AsciiChars;
+use crate::backend::DayAggregate;
+use crate::common::crc32;
+use crate::stats::{HitType, Stats};
+use crate::target_char::TargetChar;
+use chrono::{DateTime, Days, NaiveDate, NaiveDateTime};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Each profile's history is kept in a log of its own, which the history
+// database indexes: every run's events are appended to the log first, and
+// frames the database missed are added to it when the profile is loaded.
+//
+// A history log is a header followed by frames, each holding a batch of
+// events appended together:
+//
+//   header:  "TOHL" version:u8
+//   frame:   payload_len:varint payload crc32(payload):u32le
+//   payload: event*
+//   event:   char_id (zigzag(delta_ms) << 1 | hit):varint [reaction_ms:varint]
+//
+// `delta_ms` is from the previous event in the frame; the first event of a
+// frame holds its full timestamp, so every frame reads on its own. A crash
+// mid-append leaves at most one partial frame at the end, which the reader
+// drops. Timestamps are kept to the millisecond.
+const MAGIC: &[u8; 4] = b"TOHL";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 1;
+// Char ids are `AsciiChars::as_u8`, with the top bit marking numpad keys;
+// anything else follows this byte as a varint code point.
+const WIDE_CHAR: u8 = 0xFF;
+const NUMPAD_FLAG: u8 = 0x80;
+// A longer pause between two attempts is a break, not practice time.
+const MAX_PRACTICE_GAP_MS: i64 = 30_000;
+
+/// One hit or miss.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistoryEvent {
+    pub char_id: TargetChar,
+    pub result: HitType,
+}
+
+impl HistoryEvent {
+    pub fn timestamp(&self) -> NaiveDateTime {
+        match self.result {
+            HitType::Hit(timestamp, _) | HitType::Miss(timestamp) => timestamp,
+        }
+    }
+}
+
+/// A batch of events from a log, with where its frame starts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFrame {
+    pub offset: u64,
+    pub events: Vec<HistoryEvent>,
+}
+
+/// The readable part of a log.
+#[derive(Debug, Default)]
+pub struct DecodedLog {
+    pub frames: Vec<LogFrame>,
+    /// Bytes up to the end of the last good frame
+    pub valid_len: usize,
+}
+
+/// How much was practised on one day.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DayTotal {
+    pub characters: usize,
+    /// Time between attempts, leaving out breaks
+    pub practice_ms: u64,
+}
+
+impl DayTotal {
+    fn add(&mut self, event: &HistoryEvent, previous: Option<NaiveDateTime>) {
+        self.characters += 1;
+        let gap = previous.map_or(0, |previous| (event.timestamp() - previous).num_milliseconds());
+        if (0..=MAX_PRACTICE_GAP_MS).contains(&gap) {
+            self.practice_ms += gap as u64;
+        }
+    }
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn get_varint(bytes: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+fn put_char_id(out: &mut Vec<u8>, char_id: &TargetChar) {
+    match AsciiChars::from_char(char_id.as_char()) {
+        Some(ascii) if char_id.is_numpad() => out.push(ascii.as_u8() | NUMPAD_FLAG),
+        Some(ascii) => out.push(ascii.as_u8()),
+        None => {
+            out.push(WIDE_CHAR);
+            put_varint(out, char_id.as_char() as u64);
+        }
+    }
+}
+
+fn get_char_id(bytes: &[u8], pos: &mut usize) -> Option<TargetChar> {
+    let byte = *bytes.get(*pos)?;
+    *pos += 1;
+    match byte {
+        WIDE_CHAR => {
+            let code = u32::try_from(get_varint(bytes, pos)?).ok()?;
+            TargetChar::new(char::from_u32(code)?)
+        }
+        _ if byte & NUMPAD_FLAG != 0 => {
+            TargetChar::numpad(AsciiChars::from_u8(byte & !NUMPAD_FLAG)?.as_char())
+        }
+        _ => AsciiChars::from_u8(byte).map(TargetChar::from),
+    }
+}
+
+pub fn header() -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    out.push(VERSION);
+    out
+}
+
+/// Encodes a batch of events as one frame, ready to append to a log.
+pub fn encode_frame(events: &[HistoryEvent]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(events.len() * 4);
+    let mut last_ms = 0i64;
+    for event in events {
+        let ms = event.timestamp().and_utc().timestamp_millis();
+        put_char_id(&mut payload, &event.char_id);
+        let hit = matches!(event.result, HitType::Hit(..)) as u64;
+        put_varint(&mut payload, zigzag(ms - last_ms) << 1 | hit);
+        if let HitType::Hit(_, reaction_time) = event.result {
+            put_varint(&mut payload, reaction_time);
+        }
+        last_ms = ms;
+    }
+
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    put_varint(&mut frame, payload.len() as u64);
+    frame.extend_from_slice(&payload);
+    frame.extend_from_slice(&crc32(&payload).to_le_bytes());
+    frame
+}
+
+fn decode_payload(payload: &[u8], events: &mut Vec<HistoryEvent>) -> Option<()> {
+    let mut pos = 0;
+    let mut last_ms = 0i64;
+    while pos < payload.len() {
+        let char_id = get_char_id(payload, &mut pos)?;
+        let value = get_varint(payload, &mut pos)?;
+        let ms = last_ms.checked_add(unzigzag(value >> 1))?;
+        let timestamp = DateTime::from_timestamp_millis(ms)?.naive_utc();
+        let result = if value & 1 == 1 {
+            HitType::Hit(timestamp, get_varint(payload, &mut pos)?)
+        } else {
+            HitType::Miss(timestamp)
+        };
+        events.push(HistoryEvent { char_id, result });
+        last_ms = ms;
+    }
+    Some(())
+}
+
+/// Reads the frames in a log, up to the first damaged one. Errors only
+/// when the log isn't one (or is from a newer version).
+pub fn decode(bytes: &[u8]) -> Result<DecodedLog, String> {
+    if bytes.len() < HEADER_LEN {
+        // An empty log, or one whose first append was cut short.
+        if !header().starts_with(bytes) {
+            return Err("not a history log".to_string());
+        }
+        return Ok(DecodedLog::default());
+    }
+    if &bytes[..MAGIC.len()] != MAGIC {
+        return Err("not a history log".to_string());
+    }
+    if bytes[MAGIC.len()] > VERSION {
+        return Err(format!(
+            "history log version {} is newer than this app",
+            bytes[MAGIC.len()]
+        ));
+    }
+
+    let mut decoded = DecodedLog {
+        valid_len: HEADER_LEN,
+        ..DecodedLog::default()
+    };
+    let mut pos = HEADER_LEN;
+    while pos < bytes.len() {
+        let offset = pos as u64;
+        let mut events = Vec::new();
+        let frame = get_varint(bytes, &mut pos)
+            .and_then(|len| usize::try_from(len).ok())
+            .and_then(|len| {
+                let payload = bytes.get(pos..pos.checked_add(len)?)?;
+                let crc = bytes.get(pos + len..pos + len + 4)?;
+                (crc == crc32(payload).to_le_bytes()).then_some(())?;
+                decode_payload(payload, &mut events)?;
+                Some(pos + len + 4)
+            });
+        // Anything after a bad frame is lost with it.
+        let Some(end) = frame else {
+            break;
+        };
+        decoded.frames.push(LogFrame { offset, events });
+        decoded.valid_len = end;
+        pos = end;
+    }
+    Ok(decoded)
+}
+
+/// Events kept in a profile's JSON stats, from before the history log, to
+/// start its log with.
+pub fn legacy_events(stats: &Stats) -> Vec<HistoryEvent> {
+    let mut events: Vec<HistoryEvent> = stats
+        .get_records()
+        .values()
+        .flat_map(|record| {
+            record.results.iter().map(|&result| HistoryEvent {
+                char_id: record.char_id,
+                result,
+            })
+        })
+        .collect();
+    events.sort_by_key(HistoryEvent::timestamp);
+    events
+}
+
+/// The current and longest runs of consecutive practice days. The current
+/// run isn't broken until a whole day goes by without practice, so it still
+/// counts yesterday's streak if today hasn't been practised yet.
+pub fn day_streaks(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> (usize, usize) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for &day in days {
+        run = match previous {
+            Some(previous) if previous.succ_opt() == Some(day) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+
+    let mut day = if days.contains(&today) {
+        today
+    } else {
+        today - Days::new(1)
+    };
+    let mut current = 0;
+    while days.contains(&day) {
+        current += 1;
+        day = day - Days::new(1);
+    }
+    (current, longest)
+}
+
+/// A profile's practice history as totals per day: what the database had
+/// when the profile was loaded, plus everything typed since.
+#[derive(Debug, Default)]
+pub struct History {
+    days: BTreeMap<NaiveDate, DayTotal>,
+    hits: u64,
+    misses: u64,
+    /// The latest attempt, to time the gap to the next one
+    last: Option<NaiveDateTime>,
+    /// Why the database couldn't be read
+    error: Option<String>,
+}
+
+impl History {
+    pub fn from_days(aggregates: &[DayAggregate]) -> Self {
+        let mut history = History::default();
+        for aggregate in aggregates {
+            let Ok(day) = NaiveDate::parse_from_str(&aggregate.day, "%Y-%m-%d") else {
+                continue;
+            };
+            history.hits += aggregate.hits;
+            history.misses += aggregate.misses;
+            history.days.insert(
+                day,
+                DayTotal {
+                    characters: (aggregate.hits + aggregate.misses) as usize,
+                    practice_ms: aggregate.practice_ms,
+                },
+            );
+        }
+        history
+    }
+
+    pub fn unavailable(error: String) -> Self {
+        History {
+            error: Some(error),
+            ..History::default()
+        }
+    }
+
+    pub fn push(&mut self, event: &HistoryEvent) {
+        match event.result {
+            HitType::Hit(..) => self.hits += 1,
+            HitType::Miss(_) => self.misses += 1,
+        }
+        let day = event.timestamp().date();
+        let previous = self
+            .last
+            .replace(event.timestamp())
+            .filter(|previous| previous.date() == day);
+        self.days.entry(day).or_default().add(event, previous);
+    }
+
+    pub fn practice_days(&self) -> BTreeSet<NaiveDate> {
+        self.days.keys().copied().collect()
+    }
+
+    /// What was practised on each day with any practice.
+    pub fn day_totals(&self) -> BTreeMap<NaiveDate, DayTotal> {
+        self.days.clone()
+    }
+
+    pub fn day_total(&self, day: NaiveDate) -> DayTotal {
+        self.days.get(&day).copied().unwrap_or_default()
+    }
+
+    pub fn generate_html_summary(&self) -> String {
+        if let Some(error) = &self.error {
+            return format!("<p>History unavailable: {}</p>", error);
+        }
+        let Some(first) = self.days.keys().next() else {
+            return String::new();
+        };
+        let total = self.hits + self.misses;
+        format!(
+            "<p>All time: {} characters since {} ({:.1}% accuracy)</p>",
+            total,
+            first.format("%Y-%m-%d"),
+            self.hits as f64 * 100.0 / total as f64
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{at, day, hit, miss};
+
+    // A log of `frames`, with where each frame ends.
+    fn log(frames: &[Vec<HistoryEvent>]) -> (Vec<u8>, Vec<usize>) {
+        let mut bytes = header();
+        let mut ends = Vec::new();
+        for frame in frames {
+            bytes.extend(encode_frame(frame));
+            ends.push(bytes.len());
+        }
+        (bytes, ends)
+    }
+
+    // The events in every intact frame of a log.
+    fn events(bytes: &[u8]) -> Result<Vec<HistoryEvent>, String> {
+        let log = decode(bytes)?;
+        Ok(log
+            .frames
+            .into_iter()
+            .flat_map(|frame| frame.events)
+            .collect())
+    }
+
+    fn frames() -> Vec<Vec<HistoryEvent>> {
+        vec![
+            vec![hit('a', 0, 310), miss('s', 450), hit('s', 1_200, 90)],
+            // Out of order, so the deltas go negative.
+            vec![
+                hit('é', 2_000, 500),
+                miss('{', 1_500),
+                HistoryEvent {
+                    char_id: TargetChar::numpad('7').unwrap(),
+                    result: HitType::Hit(at(-86_400_000), 0),
+                },
+            ],
+            vec![hit('\n', 90_000, 1_000_000)],
+        ]
+    }
+
+    #[test]
+    fn varints_and_zigzag_round_trip() {
+        for value in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut out = Vec::new();
+            put_varint(&mut out, value);
+            assert_eq!(get_varint(&out, &mut 0), Some(value));
+        }
+        for value in [0, 1, -1, 63, -64, i64::MAX, i64::MIN] {
+            assert_eq!(unzigzag(zigzag(value)), value);
+        }
+    }
+
+    #[test]
+    fn log_round_trips() {
+        let frames = frames();
+        let (bytes, ends) = log(&frames);
+        assert_eq!(events(&bytes).unwrap(), frames.concat());
+
+        let decoded = decode(&bytes).unwrap();
+        let offsets: Vec<u64> = decoded.frames.iter().map(|frame| frame.offset).collect();
+        assert_eq!(
+            offsets,
+            [HEADER_LEN, ends[0], ends[1]].map(|end| end as u64)
+        );
+        assert_eq!(decoded.valid_len, bytes.len());
+    }
+
+    #[test]
+    fn empty_log_is_fine() {
+        assert!(events(&[]).unwrap().is_empty());
+        assert!(events(&header()).unwrap().is_empty());
+        assert_eq!(decode(&header()).unwrap().valid_len, HEADER_LEN);
+    }
+
+    #[test]
+    fn truncated_final_frame_is_dropped() {
+        let frames = frames();
+        let (bytes, ends) = log(&frames);
+        for len in ends[1] + 1..bytes.len() {
+            assert_eq!(events(&bytes[..len]).unwrap(), frames[..2].concat());
+            assert_eq!(decode(&bytes[..len]).unwrap().valid_len, ends[1]);
+        }
+    }
+
+    #[test]
+    fn bad_crc_keeps_the_frames_before_it() {
+        let frames = frames();
+        let (mut bytes, ends) = log(&frames);
+        // The last byte of the second frame's checksum.
+        bytes[ends[1] - 1] ^= 0xFF;
+        assert_eq!(events(&bytes).unwrap(), frames[0]);
+
+        // And the same for a flipped payload byte.
+        let (mut bytes, _) = log(&frames);
+        bytes[ends[0] + 2] ^= 0x01;
+        assert_eq!(events(&bytes).unwrap(), frames[0]);
+    }
+
+    #[test]
+    fn partial_header_is_an_empty_log() {
+        for len in 1..HEADER_LEN {
+            let decoded = decode(&header()[..len]).unwrap();
+            assert!(decoded.frames.is_empty());
+            // Cut back to nothing, so the next append starts the log again.
+            assert_eq!(decoded.valid_len, 0);
+        }
+    }
+
+    #[test]
+    fn wrong_header_is_not_a_log() {
+        let (mut bytes, _) = log(&frames());
+        bytes[3] = b'X';
+        assert!(decode(&bytes).is_err());
+        assert!(decode(b"TOX").is_err());
+    }
+
+    #[test]
+    fn newer_version_is_refused() {
+        let (mut bytes, _) = log(&frames());
+        bytes[MAGIC.len()] = VERSION + 1;
+        assert!(decode(&bytes).unwrap_err().contains("newer"));
+    }
+
+    #[test]
+    fn damage_anywhere_never_panics() {
+        let frames = frames();
+        let (bytes, ends) = log(&frames);
+        for i in 0..bytes.len() {
+            for flip in [0x01, 0x80, 0xFF] {
+                let mut damaged = bytes.clone();
+                damaged[i] ^= flip;
+                // Frames wholly before the damage always survive.
+                let intact = ends.iter().filter(|&&end| end <= i).count();
+                if let Ok(events) = events(&damaged) {
+                    assert!(events.starts_with(&frames[..intact].concat()));
+                }
+            }
+        }
+        for len in 0..bytes.len() {
+            let _ = decode(&bytes[..len]);
+        }
+    }
+
+    fn days(dates: &[&str]) -> BTreeSet<NaiveDate> {
+        dates.iter().map(|date| day(date)).collect()
+    }
+
+    #[test]
+    fn no_practice_is_no_streak() {
+        assert_eq!(day_streaks(&BTreeSet::new(), day("2025-03-01")), (0, 0));
+    }
+
+    #[test]
+    fn streaks_run_across_month_ends() {
+        let practised = days(&["2025-02-27", "2025-02-28", "2025-03-01"]);
+        assert_eq!(day_streaks(&practised, day("2025-03-01")), (3, 3));
+    }
+
+    #[test]
+    fn current_streak_lasts_until_a_whole_day_is_missed() {
+        let practised = days(&["2025-03-01", "2025-03-02"]);
+        // Today hasn't been practised yet, but yesterday was.
+        assert_eq!(day_streaks(&practised, day("2025-03-03")), (2, 2));
+        assert_eq!(day_streaks(&practised, day("2025-03-04")), (0, 2));
+    }
+
+    #[test]
+    fn longest_streak_can_be_an_old_one() {
+        let practised = days(&[
+            "2025-01-10",
+            "2025-01-11",
+            "2025-01-12",
+            "2025-01-13",
+            "2025-03-01",
+            "2025-03-03",
+            "2025-03-04",
+        ]);
+        assert_eq!(day_streaks(&practised, day("2025-03-04")), (2, 4));
+    }
+
+    #[test]
+    fn history_adds_to_the_database_totals() {
+        let day = at(0).date();
+        let mut history = History::from_days(&[
+            DayAggregate {
+                day: day.format("%Y-%m-%d").to_string(),
+                hits: 8,
+                misses: 2,
+                avg_ms: Some(200.0),
+                practice_ms: 5_000,
+            },
+            DayAggregate {
+                day: "someday".to_string(),
+                hits: 1,
+                misses: 0,
+                avg_ms: None,
+                practice_ms: 0,
+            },
+        ]);
+        history.push(&hit('a', 0, 100));
+        history.push(&miss('b', 1_000));
+        // A break isn't practice.
+        history.push(&hit('c', 61_000, 100));
+        let total = history.day_total(day);
+        assert_eq!(total.characters, 13);
+        assert_eq!(total.practice_ms, 6_000);
+        assert_eq!(history.practice_days().len(), 1);
+        assert!(history.generate_html_summary().contains("13 characters"));
+    }
+}
@@ -0,0 +1,104 @@
+use crate::backend::{self, CharAggregate, DayAggregate, SessionAggregate};
+use chrono::{Duration, Local};
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+// How far back the daily table goes, and how many sessions are listed.
+const RECENT_DAYS: i64 = 14;
+const RECENT_SESSIONS: u32 = 10;
+
+fn accuracy(hits: u64, misses: u64) -> String {
+    match hits + misses {
+        0 => "-".to_string(),
+        total => format!("{:.1}%", hits as f64 * 100.0 / total as f64),
+    }
+}
+
+fn avg(ms: Option<f64>) -> String {
+    ms.map(|ms| format!("{:.0}", ms)).unwrap_or_else(|| "-".to_string())
+}
+
+/// Practice history from the backend's database, queried rather than held
+/// in memory.
+#[component]
+pub fn HistoryPanel(profile: ReadSignal<String>) -> impl IntoView {
+    let (days, set_days) = signal(Vec::<DayAggregate>::new());
+    let (sessions, set_sessions) = signal(Vec::<SessionAggregate>::new());
+    let (chars, set_chars) = signal(Vec::<CharAggregate>::new());
+    let (error, set_error) = signal(None::<String>);
+
+    Effect::new(move |_| {
+        let name = profile.get();
+        spawn_local(async move {
+            let since = (Local::now() - Duration::days(RECENT_DAYS - 1))
+                .format("%Y-%m-%d")
+                .to_string();
+            let result = async {
+                set_days.set(backend::daily_aggregates(&name, Some(&since)).await?);
+                set_sessions.set(backend::session_aggregates(&name, RECENT_SESSIONS).await?);
+                set_chars.set(backend::char_aggregates(&name, None).await?);
+                Ok::<(), String>(())
+            };
+            set_error.set(result.await.err());
+        });
+    });
+
+    view! {
+        <div id="history-panel">
+            <Show when=move || error.get().is_some()>
+                <p class="hint">{move || error.get().unwrap_or_default()}</p>
+            </Show>
+            <h3>"Last " {RECENT_DAYS} " days"</h3>
+            <table>
+                <tr><th>"Day"</th><th>"Characters"</th><th>"Accuracy"</th><th>"AvgMS"</th></tr>
+                <For each=move || days.get() key=|day| day.day.clone() let:day>
+                    <tr>
+                        <td>{day.day.clone()}</td>
+                        <td>{day.hits + day.misses}</td>
+                        <td>{accuracy(day.hits, day.misses)}</td>
+                        <td>{avg(day.avg_ms)}</td>
+                    </tr>
+                </For>
+            </table>
+            <h3>"Recent sessions"</h3>
+            <table>
+                <tr>
+                    <th>"Started"</th><th>"Mode"</th><th>"Length"</th>
+                    <th>"Characters"</th><th>"Accuracy"</th><th>"AvgMS"</th>
+                </tr>
+                <For each=move || sessions.get() key=|session| session.started let:session>
+                    <tr>
+                        <td>{session.started.format("%Y-%m-%d %H:%M").to_string()}</td>
+                        <td>{session.mode.clone()}</td>
+                        <td>
+                            {
+                                let secs = (session.ended - session.started).num_seconds().max(0);
+                                format!("{}:{:02}", secs / 60, secs % 60)
+                            }
+                        </td>
+                        <td>{session.hits + session.misses}</td>
+                        <td>{accuracy(session.hits, session.misses)}</td>
+                        <td>{avg(session.avg_ms)}</td>
+                    </tr>
+                </For>
+            </table>
+            <h3>"All characters"</h3>
+            <table>
+                <tr>
+                    <th>"Character"</th><th>"Hits"</th><th>"Misses"</th>
+                    <th>"LowMS"</th><th>"HighMS"</th><th>"AvgMS"</th>
+                </tr>
+                <For each=move || chars.get() key=|row| row.char_id let:row>
+                    <tr>
+                        <td>{row.char_id.label()}</td>
+                        <td>{row.hits}</td>
+                        <td>{row.misses}</td>
+                        <td>{row.min_ms.map(|ms| ms.to_string()).unwrap_or_default()}</td>
+                        <td>{row.max_ms.map(|ms| ms.to_string()).unwrap_or_default()}</td>
+                        <td>{avg(row.avg_ms)}</td>
+                    </tr>
+                </For>
+            </table>
+        </div>
+    }
+}
@@ -0,0 +1,403 @@
+use crate::common::glyph;
+use crate::target_char::TargetChar;
+use serde::{Deserialize, Serialize};
+
+/// Physical keys of the on-screen keyboard, in the order the layout tables
+/// below list their characters. `key-intl` is the ISO key left of Z.
+pub const PHYSICAL_KEYS: [&str; 48] = [
+    "key-tilde", "key-1", "key-2", "key-3", "key-4", "key-5", "key-6", "key-7", "key-8",
+    "key-9", "key-0", "key-minus", "key-equals",
+    "key-q", "key-w", "key-e", "key-r", "key-t", "key-y", "key-u", "key-i", "key-o", "key-p",
+    "key-l-bracket", "key-r-bracket", "key-backslash",
+    "key-a", "key-s", "key-d", "key-f", "key-g", "key-h", "key-j", "key-k", "key-l",
+    "key-semicolon", "key-s-quote",
+    "key-z", "key-x", "key-c", "key-v", "key-b", "key-n", "key-m", "key-comma", "key-period",
+    "key-slash",
+    "key-intl",
+];
+
+/// `KeyboardEvent.code` of each of `PHYSICAL_KEYS`.
+pub const KEY_CODES: [&str; 48] = [
+    "Backquote", "Digit1", "Digit2", "Digit3", "Digit4", "Digit5", "Digit6", "Digit7", "Digit8",
+    "Digit9", "Digit0", "Minus", "Equal",
+    "KeyQ", "KeyW", "KeyE", "KeyR", "KeyT", "KeyY", "KeyU", "KeyI", "KeyO", "KeyP",
+    "BracketLeft", "BracketRight", "Backslash",
+    "KeyA", "KeyS", "KeyD", "KeyF", "KeyG", "KeyH", "KeyJ", "KeyK", "KeyL",
+    "Semicolon", "Quote",
+    "KeyZ", "KeyX", "KeyC", "KeyV", "KeyB", "KeyN", "KeyM", "Comma", "Period",
+    "Slash",
+    "IntlBackslash",
+];
+
+// Touch-typing finger for each of `PHYSICAL_KEYS`, as a `Finger` index.
+const STANDARD_FINGERS: &str = "001233667899901233667899990123366789901233667890";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+impl Hand {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Hand::Left => "Left",
+            Hand::Right => "Right",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Finger {
+    LeftPinky,
+    LeftRing,
+    LeftMiddle,
+    LeftIndex,
+    LeftThumb,
+    RightThumb,
+    RightIndex,
+    RightMiddle,
+    RightRing,
+    RightPinky,
+}
+
+impl Finger {
+    pub const ALL: [Finger; 10] = [
+        Finger::LeftPinky,
+        Finger::LeftRing,
+        Finger::LeftMiddle,
+        Finger::LeftIndex,
+        Finger::LeftThumb,
+        Finger::RightThumb,
+        Finger::RightIndex,
+        Finger::RightMiddle,
+        Finger::RightRing,
+        Finger::RightPinky,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Finger::LeftPinky => "left-pinky",
+            Finger::LeftRing => "left-ring",
+            Finger::LeftMiddle => "left-middle",
+            Finger::LeftIndex => "left-index",
+            Finger::LeftThumb => "left-thumb",
+            Finger::RightThumb => "right-thumb",
+            Finger::RightIndex => "right-index",
+            Finger::RightMiddle => "right-middle",
+            Finger::RightRing => "right-ring",
+            Finger::RightPinky => "right-pinky",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Finger::LeftPinky | Finger::RightPinky => "pinky",
+            Finger::LeftRing | Finger::RightRing => "ring finger",
+            Finger::LeftMiddle | Finger::RightMiddle => "middle finger",
+            Finger::LeftIndex | Finger::RightIndex => "index finger",
+            Finger::LeftThumb | Finger::RightThumb => "thumb",
+        }
+    }
+
+    pub fn hand(&self) -> Hand {
+        match self {
+            Finger::LeftPinky
+            | Finger::LeftRing
+            | Finger::LeftMiddle
+            | Finger::LeftIndex
+            | Finger::LeftThumb => Hand::Left,
+            _ => Hand::Right,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        format!("{} {}", self.hand().label(), self.label())
+    }
+}
+
+/// What the numeric keypad drill asks for; Enter is the keypad's Enter key.
+pub const NUMPAD_CHARS: &str = "0123456789+-*/.\n";
+
+// A space in a layer string means the key has nothing on that layer.
+struct KeyMap {
+    normal: &'static str,
+    shift: &'static str,
+    alt_gr: &'static str,
+    /// Characters that are dead keys: they type nothing until the next key.
+    dead: &'static str,
+    /// Finger for each physical key, as a `Finger` index
+    fingers: &'static str,
+}
+
+const US: KeyMap = KeyMap {
+    normal: "`1234567890-=qwertyuiop[]\\asdfghjkl;'zxcvbnm,./ ",
+    shift: "~!@#$%^&*()_+QWERTYUIOP{}|ASDFGHJKL:\"ZXCVBNM<>? ",
+    alt_gr: "                                                ",
+    dead: "",
+    fingers: STANDARD_FINGERS,
+};
+
+const DE: KeyMap = KeyMap {
+    normal: "^1234567890ß´qwertzuiopü+#asdfghjklöäyxcvbnm,.-<",
+    shift: "°!\"§$%&/()=?`QWERTZUIOPÜ*'ASDFGHJKLÖÄYXCVBNM;:_>",
+    alt_gr: "  ²³   {[]}\\ @ €        ~                  µ   |",
+    dead: "^´`",
+    fingers: STANDARD_FINGERS,
+};
+
+const FR: KeyMap = KeyMap {
+    normal: "²&é\"'(-è_çà)=azertyuiop^$*qsdfghjklmùwxcvbn,;:!<",
+    shift: " 1234567890°+AZERTYUIOP¨£µQSDFGHJKLM%WXCVBN?./§>",
+    alt_gr: "  ~#{[|`\\^@]}  €        ¤                       ",
+    dead: "^¨~`",
+    fingers: STANDARD_FINGERS,
+};
+
+const ES: KeyMap = KeyMap {
+    normal: "º1234567890'¡qwertyuiop`+çasdfghjklñ´zxcvbnm,.-<",
+    shift: "ª!\"·$%&/()=?¿QWERTYUIOP^*ÇASDFGHJKLÑ¨ZXCVBNM;:_>",
+    alt_gr: "\\|@#~ ¬        €       []}          {           ",
+    dead: "`´^¨",
+    fingers: STANDARD_FINGERS,
+};
+
+// Dead key, the letters it combines with, and what each one becomes.
+const COMPOSE: &[(char, &str, &str)] = &[
+    ('´', "aeiouyAEIOUY", "áéíóúýÁÉÍÓÚÝ"),
+    ('`', "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+    ('^', "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+    ('¨', "aeiouyAEIOU", "äëïöüÿÄËÏÖÜ"),
+    ('~', "aonAON", "ãõñÃÕÑ"),
+];
+
+/// One key press, with the modifiers held while pressing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyStroke {
+    /// SVG id of the physical key
+    pub key: &'static str,
+    pub shift: bool,
+    pub alt_gr: bool,
+    /// What the key types (or, for a dead key, its accent)
+    pub output: char,
+}
+
+impl KeyStroke {
+    pub fn label(&self) -> String {
+        let output = glyph(self.output);
+        if self.alt_gr {
+            format!("AltGr+{}", output)
+        } else {
+            output.to_string()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Layout {
+    Qwerty,
+    QwertzDe,
+    AzertyFr,
+    QwertyEs,
+}
+
+impl Layout {
+    pub const ALL: [Layout; 4] = [
+        Layout::Qwerty,
+        Layout::QwertzDe,
+        Layout::AzertyFr,
+        Layout::QwertyEs,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Layout::Qwerty => "qwerty",
+            Layout::QwertzDe => "qwertz_de",
+            Layout::AzertyFr => "azerty_fr",
+            Layout::QwertyEs => "qwerty_es",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Layout::Qwerty => "QWERTY (US)",
+            Layout::QwertzDe => "QWERTZ (German)",
+            Layout::AzertyFr => "AZERTY (French)",
+            Layout::QwertyEs => "QWERTY (Spanish)",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Layout::ALL.into_iter().find(|item| item.id() == id)
+    }
+
+    fn key_map(&self) -> &'static KeyMap {
+        match self {
+            Layout::Qwerty => &US,
+            Layout::QwertzDe => &DE,
+            Layout::AzertyFr => &FR,
+            Layout::QwertyEs => &ES,
+        }
+    }
+
+    /// The finger that presses `key` when touch typing.
+    pub fn finger(&self, key: &str) -> Option<Finger> {
+        let finger = match key {
+            "key-tab" | "key-caps" | "key-shift-left" => Finger::LeftPinky,
+            "key-enter" | "key-back" | "key-shift-right" => Finger::RightPinky,
+            "key-space" | "key-alt-right" => Finger::RightThumb,
+            _ => {
+                let index = PHYSICAL_KEYS.iter().position(|id| *id == key)?;
+                let finger = self.key_map().fingers.chars().nth(index)?.to_digit(10)?;
+                *Finger::ALL.get(finger as usize)?
+            }
+        };
+        Some(finger)
+    }
+
+    /// The Shift key to hold for a stroke: the one on the other hand from
+    /// the key itself.
+    pub fn stroke_shift_key(&self, stroke: &KeyStroke) -> Option<&'static str> {
+        if !stroke.shift {
+            return None;
+        }
+        match self.finger(stroke.key).map(|finger| finger.hand()) {
+            Some(Hand::Left) => Some("key-shift-right"),
+            _ => Some("key-shift-left"),
+        }
+    }
+
+    /// The (normal, shifted) characters printed on a physical key.
+    pub fn key_caps(&self, key: &str) -> Option<(char, char)> {
+        let index = PHYSICAL_KEYS.iter().position(|id| *id == key)?;
+        let map = self.key_map();
+        Some((
+            map.normal.chars().nth(index)?,
+            map.shift.chars().nth(index)?,
+        ))
+    }
+
+    fn find_stroke(&self, c: char) -> Option<KeyStroke> {
+        let map = self.key_map();
+        let layers = [
+            (map.normal, false, false),
+            (map.shift, true, false),
+            (map.alt_gr, false, true),
+        ];
+        layers.into_iter().find_map(|(layer, shift, alt_gr)| {
+            let index = layer.chars().position(|key_char| key_char == c)?;
+            Some(KeyStroke {
+                key: PHYSICAL_KEYS[index],
+                shift,
+                alt_gr,
+                output: c,
+            })
+        })
+    }
+
+    fn special_stroke(c: char) -> Option<KeyStroke> {
+        let key = match c {
+            ' ' => "key-space",
+            '\n' => "key-enter",
+            '\t' => "key-tab",
+            _ => return None,
+        };
+        Some(KeyStroke {
+            key,
+            shift: false,
+            alt_gr: false,
+            output: c,
+        })
+    }
+
+    /// The key presses that type `c` on this layout, or `None` if it can't
+    /// be typed. Accents on a dead key are typed as the dead key then Space.
+    pub fn key_sequence(&self, c: char) -> Option<Vec<KeyStroke>> {
+        if let Some(stroke) = Layout::special_stroke(c) {
+            return Some(vec![stroke]);
+        }
+        let dead = self.key_map().dead;
+        if dead.contains(c) {
+            return Some(vec![self.find_stroke(c)?, Layout::special_stroke(' ')?]);
+        }
+        if let Some(stroke) = self.find_stroke(c) {
+            return Some(vec![stroke]);
+        }
+
+        COMPOSE
+            .iter()
+            .filter(|(accent, _, _)| dead.contains(*accent))
+            .find_map(|(accent, bases, results)| {
+                let index = results.chars().position(|result| result == c)?;
+                let base = bases.chars().nth(index)?;
+                Some(vec![self.find_stroke(*accent)?, self.find_stroke(base)?])
+            })
+    }
+
+    /// The Shift key that should be held to type `c`, if any.
+    pub fn shift_key(&self, c: char) -> Option<&'static str> {
+        self.key_sequence(c)?
+            .iter()
+            .find_map(|stroke| self.stroke_shift_key(stroke))
+    }
+
+    /// Which fingers type `c`, e.g. "Right pinky, Shift with left pinky".
+    pub fn finger_hint(&self, c: char) -> Option<String> {
+        let strokes: Vec<String> = self
+            .key_sequence(c)?
+            .iter()
+            .filter_map(|stroke| {
+                let mut hint = self.finger(stroke.key)?.describe();
+                if let Some(shift) = self.stroke_shift_key(stroke).and_then(|key| self.finger(key)) {
+                    hint.push_str(&format!(", Shift with {}", shift.describe().to_lowercase()));
+                }
+                if stroke.alt_gr {
+                    hint.push_str(", AltGr with right thumb");
+                }
+                Some(hint)
+            })
+            .collect();
+        Some(strokes.join(" then "))
+    }
+
+    /// How to type `c`, for characters that need more than a plain key
+    /// (AltGr or a dead key).
+    pub fn describe(&self, c: char) -> Option<String> {
+        let sequence = self.key_sequence(c)?;
+        if sequence.len() == 1 && !sequence[0].alt_gr {
+            return None;
+        }
+        Some(
+            sequence
+                .iter()
+                .map(KeyStroke::label)
+                .collect::<Vec<String>>()
+                .join(" then "),
+        )
+    }
+
+    /// Every character this layout can type, in key order.
+    pub fn chars(&self) -> Vec<TargetChar> {
+        let map = self.key_map();
+        let mut chars: Vec<char> = vec!['\t', '\n', ' '];
+        for layer in [map.normal, map.shift, map.alt_gr] {
+            chars.extend(layer.chars().filter(|c| *c != ' '));
+        }
+        for (accent, _, results) in COMPOSE {
+            if map.dead.contains(*accent) {
+                chars.extend(results.chars());
+            }
+        }
+
+        let mut seen = Vec::new();
+        chars
+            .into_iter()
+            .filter(|c| {
+                let new = !seen.contains(c);
+                seen.push(*c);
+                new
+            })
+            .filter_map(TargetChar::new)
+            .collect()
+    }
+}
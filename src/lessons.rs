@@ -0,0 +1,576 @@
+use crate::layout::Layout;
+use crate::practice_text::plain_char;
+use crate::stats::RunSummary;
+use crate::target_char::TargetChar;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use toml::Spanned;
+
+// Characters per attempt when a lesson sets neither a length nor a time limit.
+const DEFAULT_LENGTH: usize = 50;
+
+// The built-in course, in order. Each file uses the same format as the
+// lessons users write themselves.
+const BUILTIN: &[(&str, &str)] = &[
+    ("01-home-index.toml", include_str!("../lessons/01-home-index.toml")),
+    ("02-home-row.toml", include_str!("../lessons/02-home-row.toml")),
+    ("03-home-reach.toml", include_str!("../lessons/03-home-reach.toml")),
+    ("04-top-left.toml", include_str!("../lessons/04-top-left.toml")),
+    ("05-top-right.toml", include_str!("../lessons/05-top-right.toml")),
+    ("06-bottom-row.toml", include_str!("../lessons/06-bottom-row.toml")),
+    ("07-capitals.toml", include_str!("../lessons/07-capitals.toml")),
+    ("08-numbers.toml", include_str!("../lessons/08-numbers.toml")),
+    ("09-symbols.toml", include_str!("../lessons/09-symbols.toml")),
+    ("10-sentences.toml", include_str!("../lessons/10-sentences.toml")),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LessonMode {
+    /// Random characters from the lesson's pool
+    Random,
+    /// Passages of text, typed line by line
+    Text,
+}
+
+impl LessonMode {
+    pub const ALL: [LessonMode; 2] = [LessonMode::Random, LessonMode::Text];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            LessonMode::Random => "random",
+            LessonMode::Text => "text",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        LessonMode::ALL.into_iter().find(|item| item.id() == id)
+    }
+}
+
+/// A step of the course: what to drill and the goal to reach before the
+/// lessons that follow it unlock.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lesson {
+    pub id: String,
+    pub title: String,
+    pub mode: LessonMode,
+    /// Every character drilled in random mode
+    pub chars: String,
+    /// Passages for text mode
+    pub texts: Vec<String>,
+    /// File or folder in the lessons folder to take text mode passages from instead
+    pub text_source: Option<String>,
+    /// Characters per attempt, 0 when only the time limit ends it
+    pub length: usize,
+    pub time_limit_secs: Option<u64>,
+    /// Lesson that has to be passed first
+    pub unlock_after: Option<String>,
+    /// Accuracy needed to pass, as a percentage
+    pub target_accuracy: Option<f64>,
+    /// Slowest average reaction time that passes, in milliseconds
+    pub target_avg_ms: Option<u64>,
+}
+
+// The lesson file as written, with spans kept for error messages.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LessonFile {
+    id: Spanned<String>,
+    title: String,
+    #[serde(default)]
+    mode: Option<Spanned<String>>,
+    #[serde(default)]
+    chars: Option<Spanned<String>>,
+    #[serde(default)]
+    texts: Vec<Spanned<String>>,
+    #[serde(default)]
+    text_source: Option<Spanned<String>>,
+    #[serde(default)]
+    length: Option<Spanned<usize>>,
+    #[serde(default)]
+    time_limit_secs: Option<Spanned<u64>>,
+    #[serde(default)]
+    unlock_after: Option<String>,
+    pass: Spanned<PassFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PassFile {
+    #[serde(default)]
+    accuracy: Option<Spanned<f64>>,
+    #[serde(default)]
+    avg_ms: Option<Spanned<u64>>,
+}
+
+/// A lesson file that couldn't be loaded, and the line at fault.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LessonError {
+    pub file: String,
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for LessonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.file, self.line, self.message)
+    }
+}
+
+fn line_at(text: &str, offset: usize) -> usize {
+    text.as_bytes()[..offset.min(text.len())]
+        .iter()
+        .filter(|b| **b == b'\n')
+        .count()
+        + 1
+}
+
+// Line of `key = ...`, for errors found after parsing.
+fn key_line(text: &str, key: &str) -> usize {
+    text.lines()
+        .position(|line| {
+            line.trim_start()
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+        })
+        .map_or(1, |index| index + 1)
+}
+
+pub fn parse_lesson(file: &str, text: &str) -> Result<Lesson, LessonError> {
+    let error = |offset: usize, message: String| LessonError {
+        file: file.to_string(),
+        line: line_at(text, offset),
+        message,
+    };
+
+    let lesson: LessonFile = toml::from_str(text).map_err(|e| {
+        let offset = e.span().map_or(0, |span| span.start);
+        error(offset, e.message().to_string())
+    })?;
+
+    let id = lesson.id.get_ref();
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(error(
+            lesson.id.span().start,
+            "id may only use letters, digits, '-' and '_'".to_string(),
+        ));
+    }
+
+    let mode = match &lesson.mode {
+        None => LessonMode::Random,
+        Some(mode) => LessonMode::from_id(mode.get_ref()).ok_or_else(|| {
+            error(
+                mode.span().start,
+                format!("unknown mode \"{}\", expected \"random\" or \"text\"", mode.get_ref()),
+            )
+        })?,
+    };
+
+    let chars = lesson
+        .chars
+        .as_ref()
+        .map(|chars| chars.get_ref().clone())
+        .unwrap_or_default();
+    if let Some(c) = chars.chars().find(|c| TargetChar::new(*c).is_none()) {
+        let offset = lesson.chars.as_ref().map_or(0, |chars| chars.span().start);
+        return Err(error(offset, format!("{:?} can't be typed", c)));
+    }
+    match mode {
+        LessonMode::Random if chars.is_empty() => {
+            return Err(error(
+                lesson.id.span().start,
+                "random lessons need a non-empty `chars`".to_string(),
+            ))
+        }
+        LessonMode::Text if lesson.texts.is_empty() && lesson.text_source.is_none() => {
+            return Err(error(
+                lesson.id.span().start,
+                "text lessons need `texts` or a `text_source`".to_string(),
+            ))
+        }
+        _ => {}
+    }
+    for text in &lesson.texts {
+        if text.get_ref().trim().is_empty() {
+            return Err(error(text.span().start, "texts can't be empty".to_string()));
+        }
+        // Which layout is in use isn't known yet, so any will do.
+        let untypeable = |c: &char| {
+            !c.is_whitespace()
+                && !Layout::ALL
+                    .iter()
+                    .any(|layout| layout.key_sequence(plain_char(*c)).is_some())
+        };
+        if let Some(c) = text.get_ref().chars().find(untypeable) {
+            return Err(error(
+                text.span().start,
+                format!("{:?} can't be typed on any layout", c),
+            ));
+        }
+    }
+    // Sources are read from the lessons folder, so a lesson file can't
+    // reach anything outside it.
+    if let Some(source) = &lesson.text_source {
+        let path = source.get_ref();
+        let absolute = path.starts_with(['/', '\\']) || path.get(1..2) == Some(":");
+        if path.is_empty() || absolute || path.split(['/', '\\']).any(|part| part == "..") {
+            return Err(error(
+                source.span().start,
+                "text_source must be a path inside the lessons folder".to_string(),
+            ));
+        }
+    }
+
+    if let Some(limit) = lesson.time_limit_secs.as_ref().filter(|limit| *limit.get_ref() == 0) {
+        return Err(error(limit.span().start, "time_limit_secs must be at least 1".to_string()));
+    }
+    let length = match (&lesson.length, &lesson.time_limit_secs) {
+        (Some(length), _) => *length.get_ref(),
+        (None, Some(_)) => 0,
+        (None, None) => DEFAULT_LENGTH,
+    };
+
+    let pass = lesson.pass.get_ref();
+    if let Some(accuracy) = pass.accuracy.as_ref() {
+        if !(0.0..=100.0).contains(accuracy.get_ref()) {
+            return Err(error(
+                accuracy.span().start,
+                "accuracy must be between 0 and 100".to_string(),
+            ));
+        }
+    }
+    if pass.accuracy.is_none() && pass.avg_ms.is_none() {
+        return Err(error(
+            lesson.pass.span().start,
+            "[pass] needs `accuracy`, `avg_ms` or both".to_string(),
+        ));
+    }
+
+    Ok(Lesson {
+        id: id.clone(),
+        title: lesson.title,
+        mode,
+        chars,
+        texts: lesson.texts.into_iter().map(|text| text.into_inner()).collect(),
+        text_source: lesson.text_source.map(|source| source.into_inner()),
+        length,
+        time_limit_secs: lesson.time_limit_secs.map(|limit| limit.into_inner()),
+        unlock_after: lesson.unlock_after,
+        target_accuracy: pass.accuracy.as_ref().map(|accuracy| *accuracy.get_ref()),
+        target_avg_ms: pass.avg_ms.as_ref().map(|avg_ms| *avg_ms.get_ref()),
+    })
+}
+
+/// Parses a set of lesson files, skipping (and reporting) any that are
+/// invalid, reuse an id already taken or follow a lesson that doesn't exist.
+pub fn parse_lessons<'a>(
+    files: impl IntoIterator<Item = (&'a str, &'a str)>,
+    existing: &[Lesson],
+) -> (Vec<Lesson>, Vec<LessonError>) {
+    let mut parsed: Vec<(Lesson, &str, &str)> = Vec::new();
+    let mut errors = Vec::new();
+    for (file, text) in files {
+        match parse_lesson(file, text) {
+            Ok(lesson)
+                if existing
+                    .iter()
+                    .chain(parsed.iter().map(|(l, _, _)| l))
+                    .any(|l| l.id == lesson.id) =>
+            {
+                errors.push(LessonError {
+                    file: file.to_string(),
+                    line: key_line(text, "id"),
+                    message: format!("lesson id \"{}\" is already taken", lesson.id),
+                });
+            }
+            Ok(lesson) => parsed.push((lesson, file, text)),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    // A lesson can follow one from a later file, so check once all are in.
+    // Dropping a lesson strands any that follow it, so repeat until none do.
+    let mut dropped: Vec<String> = Vec::new();
+    loop {
+        let known: Vec<String> = existing
+            .iter()
+            .chain(parsed.iter().map(|(l, _, _)| l))
+            .map(|l| l.id.clone())
+            .collect();
+        let count = parsed.len();
+        parsed.retain(|(lesson, file, text)| {
+            let Some(previous) = lesson.unlock_after.as_ref().filter(|p| !known.contains(p)) else {
+                return true;
+            };
+            let message = if dropped.contains(previous) {
+                format!("unlock_after names lesson \"{}\", which couldn't be loaded", previous)
+            } else {
+                format!("unlock_after names an unknown lesson \"{}\"", previous)
+            };
+            errors.push(LessonError {
+                file: file.to_string(),
+                line: key_line(text, "unlock_after"),
+                message,
+            });
+            dropped.push(lesson.id.clone());
+            false
+        });
+        if parsed.len() == count {
+            break;
+        }
+    }
+
+    let lessons = parsed.into_iter().map(|(lesson, _, _)| lesson).collect();
+    (lessons, errors)
+}
+
+pub fn builtin_lessons() -> Vec<Lesson> {
+    let (lessons, errors) = parse_lessons(BUILTIN.iter().copied(), &[]);
+    debug_assert!(errors.is_empty(), "built-in lessons: {:?}", errors);
+    lessons
+}
+
+impl Lesson {
+    /// The characters `rand_char()` picks from during this lesson.
+    pub fn pool(&self) -> Vec<TargetChar> {
+        let mut pool: Vec<TargetChar> = self.chars.chars().filter_map(TargetChar::new).collect();
+        pool.sort_unstable();
+        pool.dedup();
+        pool
+    }
+
+    pub fn passed_by(&self, run: &RunSummary) -> bool {
+        run.total_hits > 0
+            && self.target_accuracy.map_or(true, |target| run.accuracy >= target)
+            && self.target_avg_ms.map_or(true, |target| run.hit_avg <= target)
+    }
+
+    pub fn goal(&self) -> String {
+        let mut goals = Vec::new();
+        if let Some(accuracy) = self.target_accuracy {
+            goals.push(format!("{:.0}% accuracy", accuracy));
+        }
+        if let Some(avg_ms) = self.target_avg_ms {
+            goals.push(format!("{} ms average or faster", avg_ms));
+        }
+        let mut goal = format!("Goal: {}", goals.join(", "));
+        if let Some(limit) = self.time_limit_secs {
+            goal.push_str(&format!(" within {} s", limit));
+        }
+        goal
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LessonResult {
+    pub accuracy: f64,
+    pub avg_ms: u64,
+    pub passed: NaiveDateTime,
+}
+
+/// Which lessons a profile has passed, and how well.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LessonProgress {
+    passed: HashMap<String, LessonResult>,
+}
+
+impl LessonProgress {
+    pub fn is_passed(&self, lesson_id: &str) -> bool {
+        self.passed.contains_key(lesson_id)
+    }
+
+    /// Lessons open once the one they follow has been passed; those that
+    /// don't follow another are always open.
+    pub fn is_unlocked(&self, lesson: &Lesson) -> bool {
+        lesson
+            .unlock_after
+            .as_deref()
+            .map_or(true, |previous| self.is_passed(previous))
+    }
+
+    /// The first open lesson not yet passed, or the last one once all are.
+    pub fn next_lesson<'a>(&self, lessons: &'a [Lesson]) -> Option<&'a Lesson> {
+        lessons
+            .iter()
+            .find(|lesson| self.is_unlocked(lesson) && !self.is_passed(&lesson.id))
+            .or(lessons.last())
+    }
+
+    pub fn record(&mut self, lesson: &Lesson, run: &RunSummary, timestamp: NaiveDateTime) {
+        // Keep the best attempt rather than the latest.
+        if let Some(previous) = self.passed.get(&lesson.id) {
+            if previous.avg_ms <= run.hit_avg && previous.accuracy >= run.accuracy {
+                return;
+            }
+        }
+        self.passed.insert(
+            lesson.id.clone(),
+            LessonResult {
+                accuracy: run.accuracy,
+                avg_ms: run.hit_avg,
+                passed: timestamp,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lesson_file(id: &str, extra: &str) -> String {
+        format!(
+            "id = \"{}\"\ntitle = \"Test\"\nchars = \"asdf\"\n{}\n[pass]\naccuracy = 90\n",
+            id, extra
+        )
+    }
+
+    fn ids(lessons: &[Lesson]) -> Vec<&str> {
+        lessons.iter().map(|lesson| lesson.id.as_str()).collect()
+    }
+
+    #[test]
+    fn builtin_lessons_all_load() {
+        let (lessons, errors) = parse_lessons(BUILTIN.iter().copied(), &[]);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(lessons.len(), BUILTIN.len());
+    }
+
+    #[test]
+    fn parses_a_lesson() {
+        let text = "id = \"left\"\ntitle = \"Left hand\"\nchars = \"qwert\"\nlength = 60\n\
+                    unlock_after = \"home\"\n[pass]\naccuracy = 95\navg_ms = 900\n";
+        let lesson = parse_lesson("left.toml", text).unwrap();
+        assert_eq!(lesson.id, "left");
+        assert_eq!(lesson.mode, LessonMode::Random);
+        assert_eq!(lesson.length, 60);
+        assert_eq!(lesson.unlock_after.as_deref(), Some("home"));
+        assert_eq!(lesson.target_accuracy, Some(95.0));
+        assert_eq!(lesson.target_avg_ms, Some(900));
+    }
+
+    #[test]
+    fn time_limit_alone_means_no_length() {
+        let lesson = parse_lesson("a.toml", &lesson_file("a", "time_limit_secs = 30")).unwrap();
+        assert_eq!(lesson.length, 0);
+        assert_eq!(lesson.time_limit_secs, Some(30));
+        let lesson = parse_lesson("a.toml", &lesson_file("a", "")).unwrap();
+        assert_eq!(lesson.length, DEFAULT_LENGTH);
+    }
+
+    #[test]
+    fn errors_point_at_the_line() {
+        let error = |extra: &str| parse_lesson("bad.toml", &lesson_file("bad", extra)).unwrap_err();
+
+        let e = error("mode = \"fast\"");
+        assert_eq!((e.file.as_str(), e.line), ("bad.toml", 4));
+        assert!(e.message.contains("unknown mode"), "{}", e.message);
+
+        assert_eq!(error("length = \"long\"").line, 4);
+        assert_eq!(error("colour = \"red\"").line, 4);
+        assert_eq!(error("time_limit_secs = 0").line, 4);
+        assert_eq!(error("\n\ntime_limit_secs = 0").line, 6);
+
+        let e = parse_lesson("bad.toml", "id = \"bad id\"\ntitle = \"x\"\n[pass]\naccuracy = 9\n")
+            .unwrap_err();
+        assert_eq!(e.line, 1);
+        let e = parse_lesson("bad.toml", "id = \"x\"\ntitle = \"x\"\nchars = \"a\"\n[pass]\n")
+            .unwrap_err();
+        assert_eq!(e.line, 4);
+        assert_eq!(e.to_string(), format!("bad.toml:4: {}", e.message));
+    }
+
+    #[test]
+    fn texts_are_checked() {
+        let text = |extra: &str| {
+            let file = lesson_file("t", &format!("mode = \"text\"\n{}", extra));
+            parse_lesson("t.toml", &file)
+        };
+
+        let lesson = text("texts = [\"It\u{2019}s fine.\", \"Line two.\"]").unwrap();
+        assert_eq!(lesson.texts.len(), 2);
+        assert!(text("").is_err());
+        assert_eq!(text("texts = [\"ok\", \"  \"]").unwrap_err().line, 5);
+        let e = text("\ntexts = [\"snow \u{2603}\"]").unwrap_err();
+        assert_eq!(e.line, 6);
+        assert!(e.message.contains("can't be typed"), "{}", e.message);
+    }
+
+    #[test]
+    fn text_source_stays_in_the_lessons_folder() {
+        let source = |path: &str| {
+            let file = lesson_file("t", &format!("mode = \"text\"\ntext_source = {:?}", path));
+            parse_lesson("t.toml", &file)
+        };
+        let lesson = source("notes/intro.md").unwrap();
+        assert_eq!(lesson.text_source.as_deref(), Some("notes/intro.md"));
+        let outside = [
+            "/etc/passwd",
+            "\\\\server\\share",
+            "C:\\notes.txt",
+            "../notes",
+            "a/../../b",
+            "a\\..\\b",
+            "",
+        ];
+        for path in outside {
+            let e = source(path).unwrap_err();
+            assert_eq!(e.line, 5, "{}", path);
+        }
+    }
+
+    #[test]
+    fn duplicate_ids_are_reported_and_skipped() {
+        let first = lesson_file("a", "");
+        let second = format!("\n{}", lesson_file("a", ""));
+        let builtin = lesson_file("home", "");
+        let existing = vec![parse_lesson("home.toml", &builtin).unwrap()];
+        let third = lesson_file("home", "");
+        let (lessons, errors) = parse_lessons(
+            [("1.toml", first.as_str()), ("2.toml", second.as_str()), ("3.toml", third.as_str())],
+            &existing,
+        );
+        assert_eq!(ids(&lessons), ["a"]);
+        assert_eq!(errors.len(), 2);
+        assert_eq!((errors[0].file.as_str(), errors[0].line), ("2.toml", 2));
+        assert_eq!(errors[1].file, "3.toml");
+        assert!(errors[1].message.contains("already taken"));
+    }
+
+    #[test]
+    fn lessons_can_follow_later_files_and_existing_ones() {
+        let first = lesson_file("b", "unlock_after = \"c\"");
+        let second = lesson_file("c", "unlock_after = \"home\"");
+        let existing = vec![parse_lesson("home.toml", &lesson_file("home", "")).unwrap()];
+        let (lessons, errors) =
+            parse_lessons([("b.toml", first.as_str()), ("c.toml", second.as_str())], &existing);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(ids(&lessons), ["b", "c"]);
+    }
+
+    #[test]
+    fn orphans_are_dropped_down_the_chain() {
+        // d follows c, which follows b, which follows a lesson that doesn't exist.
+        let b = lesson_file("b", "unlock_after = \"missing\"");
+        let c = lesson_file("c", "unlock_after = \"b\"");
+        let d = lesson_file("d", "unlock_after = \"c\"");
+        let e = lesson_file("e", "");
+        let (lessons, errors) = parse_lessons(
+            [
+                ("d.toml", d.as_str()),
+                ("c.toml", c.as_str()),
+                ("b.toml", b.as_str()),
+                ("e.toml", e.as_str()),
+            ],
+            &[],
+        );
+        assert_eq!(ids(&lessons), ["e"]);
+        let files: Vec<&str> = errors.iter().map(|e| e.file.as_str()).collect();
+        assert_eq!(files, ["b.toml", "c.toml", "d.toml"]);
+        assert!(errors.iter().all(|e| e.line == 4));
+        assert!(errors[0].message.contains("unknown lesson \"missing\""));
+        assert!(errors[1].message.contains("\"b\", which couldn't be loaded"));
+    }
+}
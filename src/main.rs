@@ -1,18 +1,48 @@
+mod achievements;
+mod achievements_panel;
 mod app;
+mod calendar;
+mod char_names;
 mod ascii_chars;
+mod backend;
 mod common;
+mod ghost;
+mod history;
+mod history_panel;
+mod layout;
+mod lessons;
+mod personal_best;
+mod practice_text;
+mod profile;
+mod race;
+mod race_panel;
+mod recording;
+mod replay;
+mod settings;
+mod settings_panel;
+mod snippets;
+mod sound;
 mod stats;
 mod svg_keyboard;
+mod target_char;
 mod timer;
+#[cfg(test)]
+mod test_fixtures;
 
 use app::*;
 use leptos::prelude::*;
 
 fn main() {
     console_error_panic_hook::set_once();
-    mount_to_body(|| {
-        view! {
-            <App/>
-        }
-    })
+    // Settings have to be in place before the first render, so the active
+    // profile is loaded before mounting.
+    wasm_bindgen_futures::spawn_local(async {
+        let profile = backend::load_startup_profile().await;
+        profile.settings.apply();
+        mount_to_body(move || {
+            view! {
+                <App profile/>
+            }
+        })
+    });
 }
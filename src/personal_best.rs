@@ -0,0 +1,354 @@
+use crate::stats::RunSummary;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Fewest characters a run needs before its average and accuracy can count as a record.
+pub const MIN_RUN_LENGTH: usize = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordKind {
+    FastestAverage,
+    HighestAccuracy,
+    LongestStreak,
+}
+
+impl RecordKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RecordKind::FastestAverage => "Fastest average",
+            RecordKind::HighestAccuracy => "Highest accuracy",
+            RecordKind::LongestStreak => "Longest streak",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PersonalBest {
+    /// Lowest average reaction time in milliseconds over a qualifying run
+    pub fastest_avg_ms: Option<u64>,
+    /// Highest hit percentage over a qualifying run
+    pub best_accuracy: Option<f64>,
+    /// Most hits in a row without a miss
+    pub longest_streak: usize,
+    pub updated: Option<NaiveDateTime>,
+    /// Recording of the run with the fastest average, to race as a ghost
+    #[serde(default)]
+    pub fastest_recording: Option<String>,
+}
+
+/// Personal bests for one profile, keyed by practice mode (character set + layout).
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PersonalBests {
+    bests: HashMap<String, PersonalBest>,
+}
+
+impl PersonalBests {
+    pub fn new() -> Self {
+        PersonalBests {
+            bests: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, mode: &str) -> Option<&PersonalBest> {
+        self.bests.get(mode)
+    }
+
+    pub fn set(&mut self, mode: &str, best: PersonalBest) {
+        self.bests.insert(mode.to_string(), best);
+    }
+
+    pub fn update(
+        &mut self,
+        mode: &str,
+        run: &RunSummary,
+        timestamp: NaiveDateTime,
+    ) -> Vec<RecordKind> {
+        let best = self.bests.entry(mode.to_string()).or_default();
+        let mut new_records = Vec::new();
+
+        if run.total_hits + run.total_misses >= MIN_RUN_LENGTH {
+            if run.total_hits > 0 && best.fastest_avg_ms.map_or(true, |ms| run.hit_avg < ms) {
+                best.fastest_avg_ms = Some(run.hit_avg);
+                new_records.push(RecordKind::FastestAverage);
+            }
+            if best.best_accuracy.map_or(true, |acc| run.accuracy > acc) {
+                best.best_accuracy = Some(run.accuracy);
+                new_records.push(RecordKind::HighestAccuracy);
+            }
+        }
+
+        if run.longest_streak > best.longest_streak {
+            best.longest_streak = run.longest_streak;
+            new_records.push(RecordKind::LongestStreak);
+        }
+
+        if !new_records.is_empty() {
+            best.updated = Some(timestamp);
+        }
+        new_records
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub profile: String,
+    pub mode: String,
+    pub best: PersonalBest,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    pub fn upsert(&mut self, entry: LeaderboardEntry) {
+        match self
+            .entries
+            .iter_mut()
+            .find(|e| e.profile == entry.profile && e.mode == entry.mode)
+        {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    pub fn remove_profile(&mut self, profile: &str) {
+        self.entries.retain(|e| e.profile != profile);
+    }
+
+    /// Entries for one mode, fastest average first. Profiles without a
+    /// qualifying run are listed last, ordered by streak.
+    pub fn ranked(&self, mode: &str) -> Vec<&LeaderboardEntry> {
+        let mut entries: Vec<&LeaderboardEntry> =
+            self.entries.iter().filter(|e| e.mode == mode).collect();
+        entries.sort_by(|a, b| {
+            let a_ms = a.best.fastest_avg_ms.unwrap_or(u64::MAX);
+            let b_ms = b.best.fastest_avg_ms.unwrap_or(u64::MAX);
+            a_ms.cmp(&b_ms)
+                .then(b.best.longest_streak.cmp(&a.best.longest_streak))
+        });
+        entries
+    }
+
+    pub fn generate_html_report(&self, mode: &str, profile: &str) -> String {
+        let mut html = String::with_capacity(1024);
+        html.push_str("<h2>Leaderboard</h2><table border='1'>");
+        html.push_str("<tr><th>#</th><th>Profile</th><th>AvgMS</th><th>Accuracy</th><th>Streak</th></tr>");
+
+        for (rank, entry) in self.ranked(mode).iter().enumerate() {
+            let row_class = if entry.profile == profile {
+                " class='leaderboard-self'"
+            } else {
+                ""
+            };
+            html.push_str(&format!(
+                "<tr{}><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                row_class,
+                rank + 1,
+                entry.profile,
+                entry
+                    .best
+                    .fastest_avg_ms
+                    .map_or("-".to_string(), |ms| ms.to_string()),
+                entry
+                    .best
+                    .best_accuracy
+                    .map_or("-".to_string(), |acc| format!("{:.1}%", acc)),
+                entry.best.longest_streak
+            ));
+        }
+
+        html.push_str("</table>");
+        html
+    }
+}
+
+pub fn generate_html_bests(best: &PersonalBest, new_records: &[RecordKind]) -> String {
+    let mut html = String::with_capacity(512);
+    html.push_str("<h2>Personal Bests</h2><table border='1'>");
+
+    let rows = [
+        (
+            RecordKind::FastestAverage,
+            best.fastest_avg_ms
+                .map_or("-".to_string(), |ms| format!("{} ms", ms)),
+        ),
+        (
+            RecordKind::HighestAccuracy,
+            best.best_accuracy
+                .map_or("-".to_string(), |acc| format!("{:.1}%", acc)),
+        ),
+        (RecordKind::LongestStreak, best.longest_streak.to_string()),
+    ];
+
+    for (kind, value) in rows {
+        if new_records.contains(&kind) {
+            html.push_str(&format!(
+                "<tr class='new-record'><td>{}</td><td>{}</td><td>New record!</td></tr>",
+                kind.label(),
+                value
+            ));
+        } else {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td></td></tr>",
+                kind.label(),
+                value
+            ));
+        }
+    }
+
+    html.push_str("</table>");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::at;
+
+    fn run(hits: usize, misses: usize, hit_avg: u64, longest_streak: usize) -> RunSummary {
+        RunSummary {
+            total_hits: hits,
+            total_misses: misses,
+            hit_avg,
+            accuracy: hits as f64 * 100.0 / (hits + misses).max(1) as f64,
+            longest_streak,
+        }
+    }
+
+    fn entry(
+        profile: &str,
+        mode: &str,
+        fastest_avg_ms: Option<u64>,
+        streak: usize,
+    ) -> LeaderboardEntry {
+        LeaderboardEntry {
+            profile: profile.to_string(),
+            mode: mode.to_string(),
+            best: PersonalBest {
+                fastest_avg_ms,
+                longest_streak: streak,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn first_qualifying_run_sets_every_record() {
+        let mut bests = PersonalBests::new();
+        let records = bests.update("mode", &run(30, 0, 400, 30), at(0));
+        assert_eq!(
+            records,
+            [
+                RecordKind::FastestAverage,
+                RecordKind::HighestAccuracy,
+                RecordKind::LongestStreak
+            ]
+        );
+        let best = bests.get("mode").unwrap();
+        assert_eq!(best.fastest_avg_ms, Some(400));
+        assert_eq!(best.best_accuracy, Some(100.0));
+        assert_eq!(best.updated, Some(at(0)));
+    }
+
+    #[test]
+    fn short_runs_only_count_for_streaks() {
+        let mut bests = PersonalBests::new();
+        let records = bests.update("mode", &run(10, 0, 300, 10), at(0));
+        assert_eq!(records, [RecordKind::LongestStreak]);
+        let best = bests.get("mode").unwrap();
+        assert_eq!(best.fastest_avg_ms, None);
+        assert_eq!(best.best_accuracy, None);
+    }
+
+    #[test]
+    fn only_improvements_are_records() {
+        let mut bests = PersonalBests::new();
+        bests.update("mode", &run(40, 10, 400, 20), at(0));
+
+        // Slower, less accurate and a shorter streak: nothing changes.
+        assert!(bests.update("mode", &run(30, 20, 500, 5), at(1)).is_empty());
+        assert_eq!(bests.get("mode").unwrap().updated, Some(at(0)));
+
+        // Faster but no more accurate, and the same streak.
+        let records = bests.update("mode", &run(40, 10, 350, 20), at(2));
+        assert_eq!(records, [RecordKind::FastestAverage]);
+        let best = bests.get("mode").unwrap();
+        assert_eq!(best.fastest_avg_ms, Some(350));
+        assert_eq!(best.updated, Some(at(2)));
+    }
+
+    #[test]
+    fn modes_keep_separate_bests() {
+        let mut bests = PersonalBests::new();
+        bests.update("a", &run(30, 0, 400, 30), at(0));
+        assert_eq!(bests.update("b", &run(30, 0, 500, 30), at(1)).len(), 3);
+        assert_eq!(bests.get("a").unwrap().fastest_avg_ms, Some(400));
+        assert_eq!(bests.get("b").unwrap().fastest_avg_ms, Some(500));
+    }
+
+    #[test]
+    fn ranked_puts_fastest_first_and_unqualified_last() {
+        let mut board = Leaderboard::default();
+        board.upsert(entry("slow", "mode", Some(500), 50));
+        board.upsert(entry("none-short", "mode", None, 5));
+        board.upsert(entry("fast", "mode", Some(300), 10));
+        board.upsert(entry("none-long", "mode", None, 20));
+        board.upsert(entry("other", "other-mode", Some(100), 100));
+
+        let ranked: Vec<&str> = board
+            .ranked("mode")
+            .iter()
+            .map(|e| e.profile.as_str())
+            .collect();
+        assert_eq!(ranked, ["fast", "slow", "none-long", "none-short"]);
+    }
+
+    #[test]
+    fn upsert_replaces_a_profiles_entry_for_the_mode() {
+        let mut board = Leaderboard::default();
+        board.upsert(entry("alice", "mode", Some(500), 5));
+        board.upsert(entry("alice", "other-mode", Some(400), 5));
+        board.upsert(entry("alice", "mode", Some(300), 5));
+        assert_eq!(board.entries.len(), 2);
+        assert_eq!(board.ranked("mode")[0].best.fastest_avg_ms, Some(300));
+    }
+
+    #[test]
+    fn remove_profile_drops_all_its_entries() {
+        let mut board = Leaderboard::default();
+        board.upsert(entry("alice", "mode", Some(500), 5));
+        board.upsert(entry("alice", "other-mode", Some(400), 5));
+        board.upsert(entry("bob", "mode", Some(300), 5));
+        board.remove_profile("alice");
+        assert_eq!(board.entries.len(), 1);
+        assert_eq!(board.entries[0].profile, "bob");
+    }
+
+    #[test]
+    fn html_bests_flags_new_records() {
+        let best = PersonalBest {
+            fastest_avg_ms: Some(321),
+            best_accuracy: Some(97.54),
+            longest_streak: 42,
+            ..Default::default()
+        };
+        let html = generate_html_bests(&best, &[RecordKind::HighestAccuracy]);
+        assert!(html.contains("<tr><td>Fastest average</td><td>321 ms</td><td></td></tr>"));
+        assert!(html.contains(
+            "<tr class='new-record'><td>Highest accuracy</td><td>97.5%</td><td>New record!</td></tr>"
+        ));
+        assert!(html.contains("<tr><td>Longest streak</td><td>42</td><td></td></tr>"));
+        assert_eq!(html.matches("New record!").count(), 1);
+    }
+
+    #[test]
+    fn html_bests_shows_dashes_before_a_qualifying_run() {
+        let html = generate_html_bests(&PersonalBest::default(), &[]);
+        assert!(html.contains("<td>Fastest average</td><td>-</td>"));
+        assert!(html.contains("<td>Highest accuracy</td><td>-</td>"));
+        assert!(html.contains("<td>Longest streak</td><td>0</td>"));
+    }
+}
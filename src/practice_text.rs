@@ -0,0 +1,158 @@
+use crate::layout::Layout;
+use crate::target_char::TargetChar;
+
+// Passages are wrapped to this width for display and kept to roughly this length.
+pub const LINE_WIDTH: usize = 60;
+pub const PASSAGE_LEN: usize = 300;
+
+/// Typographic punctuation as the ASCII a keyboard can actually type.
+pub fn plain_char(c: char) -> char {
+    match c {
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{2032}' => '\'',
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{2033}' => '"',
+        '\u{2010}'..='\u{2015}' | '\u{2212}' => '-',
+        '\u{00A0}' | '\u{2002}'..='\u{200A}' | '\t' => ' ',
+        '\u{2026}' => '.',
+        _ => c,
+    }
+}
+
+fn map_char(c: char, layout: &Layout) -> Option<char> {
+    // Anything but plain spacing is kept only if the layout can type it.
+    let c = plain_char(c);
+    match c {
+        ' ' | '\n' => Some(c),
+        _ if TargetChar::new(c).is_some() && layout.key_sequence(c).is_some() => Some(c),
+        _ => None,
+    }
+}
+
+fn clean_markdown_line(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("```") || trimmed.starts_with("---") || trimmed.starts_with("<!--") {
+        return None;
+    }
+    Some(
+        trimmed
+            .trim_start_matches('#')
+            .trim_start_matches('>')
+            .trim_start(),
+    )
+}
+
+pub fn normalize(text: &str, layout: &Layout) -> String {
+    text.replace("\r\n", "\n")
+        .chars()
+        .filter_map(|c| map_char(c, layout))
+        .collect()
+}
+
+/// Splits text into passages on blank lines, joining short paragraphs and
+/// breaking long ones at word boundaries so each is about `PASSAGE_LEN` long.
+/// Characters `layout` can't type are dropped.
+pub fn split_passages(text: &str, layout: &Layout) -> Vec<String> {
+    let normalized = normalize(text, layout);
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for line in normalized.lines() {
+        let Some(line) = clean_markdown_line(line) else {
+            continue;
+        };
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        for word in line.split_whitespace() {
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+
+    let mut passages = Vec::new();
+    let mut passage = String::new();
+    for paragraph in paragraphs {
+        for word in paragraph.split(' ') {
+            if passage.chars().count() + word.chars().count() + 1 > PASSAGE_LEN
+                && !passage.is_empty()
+            {
+                passages.push(std::mem::take(&mut passage));
+            }
+            if !passage.is_empty() {
+                passage.push(' ');
+            }
+            passage.push_str(word);
+        }
+        // Short paragraphs are merged with the next one.
+        if passage.chars().count() >= PASSAGE_LEN / 2 {
+            passages.push(std::mem::take(&mut passage));
+        }
+    }
+    if !passage.is_empty() {
+        passages.push(passage);
+    }
+    passages
+}
+
+/// Wraps a passage into display lines of at most `LINE_WIDTH` characters.
+pub fn wrap(passage: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in passage.split(' ') {
+        if !line.is_empty() && line.chars().count() + word.chars().count() + 1 > LINE_WIDTH {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_keeps_what_the_layout_can_type() {
+        assert_eq!(
+            normalize("Straße \u{201C}ok\u{201D}", &Layout::Qwerty),
+            "Strae \"ok\""
+        );
+        assert_eq!(
+            normalize("Straße \u{201C}ok\u{201D}", &Layout::QwertzDe),
+            "Straße \"ok\""
+        );
+    }
+
+    #[test]
+    fn wrap_counts_characters_not_bytes() {
+        // A full line of 60 characters, but 118 bytes.
+        let word = "é".repeat(57);
+        let line = format!("{} éé", word);
+        assert_eq!(wrap(&line), line);
+        assert_eq!(wrap(&format!("{} ééé", word)), format!("{}\nééé", word));
+    }
+
+    #[test]
+    fn passages_are_measured_in_characters() {
+        let text = vec!["äöü"; 150].join(" ");
+        let passages = split_passages(&text, &Layout::QwertzDe);
+        assert!(passages.len() > 1);
+        for passage in &passages {
+            assert!(passage.chars().count() <= PASSAGE_LEN);
+            assert!(passage.chars().count() > PASSAGE_LEN - 4);
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use crate::achievements::Achievements;
+use crate::lessons::LessonProgress;
+use crate::personal_best::PersonalBests;
+use crate::settings::Settings;
+use crate::stats::Stats;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_PROFILE: &str = "default";
+pub const MAX_NAME_LEN: usize = 32;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    /// The run under way. Only read from older saves, whose stats held
+    /// history that has since moved to the history database.
+    #[serde(default = "Stats::new", skip_serializing)]
+    pub stats: Stats,
+    #[serde(default)]
+    pub bests: PersonalBests,
+    #[serde(default)]
+    pub settings: Settings,
+    #[serde(default)]
+    pub lessons: LessonProgress,
+    #[serde(default)]
+    pub achievements: Achievements,
+}
+
+impl Profile {
+    pub fn new(name: &str) -> Self {
+        Profile {
+            name: name.to_string(),
+            stats: Stats::new(),
+            bests: PersonalBests::new(),
+            settings: Settings::default(),
+            lessons: LessonProgress::default(),
+            achievements: Achievements::default(),
+        }
+    }
+
+    /// Mirrors the backend's `check_name`, which has the final say, so a
+    /// name can be turned down as it's typed; change the two together.
+    pub fn is_valid_name(name: &str) -> bool {
+        // Profile names double as file names in the app data directory.
+        !name.is_empty()
+            && name.len() <= MAX_NAME_LEN
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == ' ')
+            && name.trim() == name
+    }
+}
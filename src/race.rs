@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+// Just the parts of the browser's WebSocket the race client needs.
+#[wasm_bindgen]
+extern "C" {
+    #[derive(Clone)]
+    type WebSocket;
+    #[wasm_bindgen(constructor, catch)]
+    fn new(url: &str) -> Result<WebSocket, JsValue>;
+    #[wasm_bindgen(method, catch)]
+    fn send(this: &WebSocket, data: &str) -> Result<(), JsValue>;
+    #[wasm_bindgen(method)]
+    fn close(this: &WebSocket);
+    #[wasm_bindgen(method, setter = onopen)]
+    fn set_onopen(this: &WebSocket, handler: Option<&js_sys::Function>);
+    #[wasm_bindgen(method, setter = onmessage)]
+    fn set_onmessage(this: &WebSocket, handler: Option<&js_sys::Function>);
+    #[wasm_bindgen(method, setter = onclose)]
+    fn set_onclose(this: &WebSocket, handler: Option<&js_sys::Function>);
+
+    type MessageEvent;
+    #[wasm_bindgen(method, getter)]
+    fn data(this: &MessageEvent) -> JsValue;
+}
+
+// The protocol is JSON text frames, tagged by "type"; the backend's
+// `race.rs` has the other half.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Join { name: String },
+    /// Races everyone connected on `sequence`
+    Start { sequence: String },
+    /// Characters typed so far, and misses on the way
+    Progress { position: usize, misses: usize },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Sent to this client alone, with its player id
+    Welcome { id: u32 },
+    /// Everyone connected, and the length of the race under way or last run
+    Players { players: Vec<Player>, length: usize },
+    Start { sequence: String, countdown_ms: u64 },
+    /// Every racer has finished or run out of time; best first, with
+    /// those who didn't finish last
+    Results { standings: Vec<Player> },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Player {
+    pub id: u32,
+    pub name: String,
+    /// Whether they're in the race under way, rather than watching
+    pub racing: bool,
+    pub position: usize,
+    pub misses: usize,
+    /// Milliseconds from the start to typing the last character
+    pub finished_ms: Option<u64>,
+}
+
+impl Player {
+    /// Percentage of keypresses that were hits.
+    pub fn accuracy(&self) -> f64 {
+        match self.position + self.misses {
+            0 => 0.0,
+            total => self.position as f64 * 100.0 / total as f64,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum RaceEvent {
+    Connected,
+    Message(ServerMessage),
+    Disconnected,
+}
+
+fn encode(message: &ClientMessage) -> Result<String, JsValue> {
+    let value = message
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(JsValue::from)?;
+    js_sys::JSON::stringify(&value).map(String::from)
+}
+
+fn decode(data: JsValue) -> Option<ServerMessage> {
+    let value = js_sys::JSON::parse(&data.as_string()?).ok()?;
+    serde_wasm_bindgen::from_value(value).ok()
+}
+
+/// A connection to a race server. Dropping it leaves the race.
+pub struct RaceClient {
+    socket: WebSocket,
+    // Kept alive for as long as the socket can call them.
+    _handlers: [Closure<dyn FnMut(JsValue)>; 3],
+}
+
+impl RaceClient {
+    /// Connects to the server at `url` (ws://host:port), joining as `name`
+    /// once the connection opens.
+    pub fn connect(
+        url: &str,
+        name: String,
+        on_event: impl Fn(RaceEvent) + 'static,
+    ) -> Result<RaceClient, String> {
+        let socket = WebSocket::new(url)
+            .map_err(|e| e.as_string().unwrap_or_else(|| format!("Can't connect to {}", url)))?;
+        let on_event = Rc::new(on_event);
+
+        let joiner = socket.clone();
+        let on_open = {
+            let on_event = on_event.clone();
+            Closure::<dyn FnMut(JsValue)>::new(move |_| {
+                if let Ok(text) = encode(&ClientMessage::Join { name: name.clone() }) {
+                    let _ = joiner.send(&text);
+                }
+                on_event(RaceEvent::Connected);
+            })
+        };
+        let on_message = {
+            let on_event = on_event.clone();
+            Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                let event = MessageEvent::unchecked_from_js(event);
+                if let Some(message) = decode(event.data()) {
+                    on_event(RaceEvent::Message(message));
+                }
+            })
+        };
+        let on_close = Closure::<dyn FnMut(JsValue)>::new(move |_| {
+            on_event(RaceEvent::Disconnected);
+        });
+
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+        Ok(RaceClient {
+            socket,
+            _handlers: [on_open, on_message, on_close],
+        })
+    }
+
+    pub fn send(&self, message: &ClientMessage) {
+        if let Ok(text) = encode(message) {
+            let _ = self.socket.send(&text);
+        }
+    }
+}
+
+impl Drop for RaceClient {
+    fn drop(&mut self) {
+        // The handlers go with the client, so the socket mustn't call them.
+        self.socket.set_onopen(None);
+        self.socket.set_onmessage(None);
+        self.socket.set_onclose(None);
+        self.socket.close();
+    }
+}
@@ -0,0 +1,401 @@
+use crate::backend::{self, RaceServer};
+use crate::common::{glyph, random_index};
+use crate::race::{ClientMessage, Player, RaceClient, RaceEvent, ServerMessage};
+use crate::settings::Settings;
+use crate::sound::{self, Sound};
+use leptos::task::spawn_local;
+use leptos::web_sys::InputEvent;
+use leptos::{ev::Event, html, prelude::*};
+use std::time::Duration;
+use wasm_bindgen::JsCast;
+
+const DEFAULT_PORT: u16 = 7878;
+const DEFAULT_LENGTH: usize = 50;
+// The server turns down anything longer.
+const MAX_LENGTH: usize = 1000;
+
+// Characters from the chosen set; enter and tab can't go in the race box.
+fn race_sequence(settings: &Settings, length: usize) -> String {
+    let pool: Vec<char> = settings
+        .char_set
+        .pool(settings.layout)
+        .iter()
+        .map(|target| target.as_char())
+        .filter(|c| *c != '\n' && *c != '\t')
+        .collect();
+    if pool.is_empty() {
+        return String::new();
+    }
+    (0..length).map(|_| pool[random_index(pool.len())]).collect()
+}
+
+fn seconds(ms: u64) -> String {
+    format!("{:.1}s", ms as f64 / 1000.0)
+}
+
+fn shown(chars: &[char]) -> String {
+    chars.iter().map(|c| glyph(*c)).collect()
+}
+
+/// Races against other players on the LAN, through a server one of them
+/// hosts.
+#[component]
+pub fn RacePanel(profile: ReadSignal<String>, settings: ReadSignal<Settings>) -> impl IntoView {
+    let client = StoredValue::new_local(None::<RaceClient>);
+    let (server, set_server) = signal(None::<RaceServer>);
+    let (port, set_port) = signal(DEFAULT_PORT.to_string());
+    let (url, set_url) = signal(format!("ws://localhost:{}", DEFAULT_PORT));
+    let (name, set_name) = signal(profile.get_untracked());
+    let (length, set_length) = signal(DEFAULT_LENGTH.to_string());
+    let (connected, set_connected) = signal(false);
+    let (my_id, set_my_id) = signal(None::<u32>);
+    let (players, set_players) = signal(Vec::<Player>::new());
+    let (race_length, set_race_length) = signal(0usize);
+    // This client's copy of the race under way, once it has one.
+    let (sequence, set_sequence) = signal(Vec::<char>::new());
+    let (countdown, set_countdown) = signal(0u64);
+    let (position, set_position) = signal(0usize);
+    let (misses, set_misses) = signal(0usize);
+    let (standings, set_standings) = signal(None::<Vec<Player>>);
+    let (error, set_error) = signal(None::<String>);
+    let input_ref = NodeRef::<html::Input>::new();
+
+    let reset = move || {
+        set_connected.set(false);
+        set_my_id.set(None);
+        set_players.set(Vec::new());
+        set_sequence.set(Vec::new());
+        set_countdown.set(0);
+        set_standings.set(None);
+    };
+
+    let start_race = move |text: String, countdown_ms: u64| {
+        set_sequence.set(text.chars().collect());
+        set_position.set(0);
+        set_misses.set(0);
+        set_standings.set(None);
+        // Count down whole seconds, landing on zero as typing opens.
+        let secs = countdown_ms.div_ceil(1000);
+        set_countdown.set(secs);
+        for step in 1..=secs {
+            set_timeout(
+                move || set_countdown.set(secs - step),
+                Duration::from_millis(countdown_ms - (secs - step) * 1000),
+            );
+        }
+        // Once the race box is on the page.
+        set_timeout(
+            move || {
+                if let Some(input) = input_ref.get_untracked() {
+                    let _ = input.focus();
+                }
+            },
+            Duration::ZERO,
+        );
+    };
+
+    let on_event = move |event: RaceEvent| match event {
+        RaceEvent::Connected => {
+            set_connected.set(true);
+            set_error.set(None);
+        }
+        RaceEvent::Disconnected => {
+            set_error.set(Some(if connected.get_untracked() {
+                "Lost the connection to the race server.".to_string()
+            } else {
+                "Couldn't connect to the race server.".to_string()
+            }));
+            reset();
+        }
+        RaceEvent::Message(ServerMessage::Welcome { id }) => set_my_id.set(Some(id)),
+        RaceEvent::Message(ServerMessage::Players { players, length }) => {
+            set_players.set(players);
+            set_race_length.set(length);
+        }
+        RaceEvent::Message(ServerMessage::Start {
+            sequence,
+            countdown_ms,
+        }) => start_race(sequence, countdown_ms),
+        RaceEvent::Message(ServerMessage::Results { standings }) => {
+            set_standings.set(Some(standings))
+        }
+    };
+
+    let join = move |url: String| {
+        client.set_value(None);
+        reset();
+        match RaceClient::connect(&url, name.get_untracked(), on_event) {
+            Ok(connection) => client.set_value(Some(connection)),
+            Err(e) => set_error.set(Some(e)),
+        }
+    };
+
+    let leave = move || {
+        client.set_value(None);
+        reset();
+    };
+
+    let host = move |_| {
+        let port = port.get_untracked().trim().parse::<u16>().ok();
+        spawn_local(async move {
+            match backend::start_race_server(port).await {
+                Ok(info) => {
+                    // The host races too, over the same server as everyone else.
+                    if let Some(local) = info.urls.first() {
+                        set_url.set(local.clone());
+                        join(local.clone());
+                    }
+                    set_server.set(Some(info));
+                }
+                Err(e) => set_error.set(Some(e)),
+            }
+        });
+    };
+
+    let stop_hosting = move |_| {
+        leave();
+        spawn_local(async move {
+            match backend::stop_race_server().await {
+                Ok(()) => set_server.set(None),
+                Err(e) => set_error.set(Some(e)),
+            }
+        });
+    };
+
+    let send_start = move |_| {
+        let length = length
+            .get_untracked()
+            .trim()
+            .parse::<usize>()
+            .unwrap_or(DEFAULT_LENGTH)
+            .clamp(1, MAX_LENGTH);
+        let sequence = race_sequence(&settings.get_untracked(), length);
+        if sequence.is_empty() {
+            set_error.set(Some("The character set has nothing to race on.".to_string()));
+            return;
+        }
+        client.with_value(|client| {
+            if let Some(client) = client {
+                client.send(&ClientMessage::Start { sequence });
+            }
+        });
+    };
+
+    let type_char = move || {
+        let Some(input) = input_ref.get_untracked() else {
+            return;
+        };
+        let typed = input.value();
+        input.set_value("");
+        let Some(c) = typed.chars().last() else {
+            return;
+        };
+        let sequence = sequence.get_untracked();
+        let at = position.get_untracked();
+        if countdown.get_untracked() > 0 || at >= sequence.len() {
+            return;
+        }
+        let settings = settings.get_untracked();
+        if c == sequence[at] {
+            set_position.set(at + 1);
+            sound::play(&settings, Sound::Hit);
+        } else {
+            set_misses.update(|misses| *misses += 1);
+            sound::play(&settings, Sound::Miss);
+        }
+        client.with_value(|client| {
+            if let Some(client) = client {
+                client.send(&ClientMessage::Progress {
+                    position: position.get_untracked(),
+                    misses: misses.get_untracked(),
+                });
+            }
+        });
+    };
+
+    let racing = move || !sequence.get().is_empty();
+    let under_way = move || {
+        standings.get().is_none() && players.get().iter().any(|p| p.racing && p.finished_ms.is_none())
+    };
+    let finished = move || racing() && position.get() >= sequence.get().len();
+
+    view! {
+        <div id="race-panel">
+            <Show when=move || error.get().is_some()>
+                <p class="hint" role="alert">{move || error.get().unwrap_or_default()}</p>
+            </Show>
+            <div class="row race-host">
+                <label for="race-port">"Port"</label>
+                <input
+                    id="race-port"
+                    type="number"
+                    min="1"
+                    max="65535"
+                    prop:value=move || port.get()
+                    disabled=move || server.get().is_some()
+                    on:input=move |ev| set_port.set(event_target_value(&ev))/>
+                <Show
+                    when=move || server.get().is_some()
+                    fallback=move || view! {
+                        <button type="button" on:click=host>"Host race"</button>
+                    }>
+                    <button type="button" on:click=stop_hosting>"Stop hosting"</button>
+                </Show>
+            </div>
+            <Show when=move || server.get().is_some()>
+                <p class="hint">
+                    "Others can join at "
+                    {move || server.get().map(|info| info.urls.join(" or ")).unwrap_or_default()}
+                </p>
+            </Show>
+            <div class="row race-join">
+                <label for="race-url">"Server"</label>
+                <input
+                    id="race-url"
+                    prop:value=move || url.get()
+                    disabled=move || connected.get()
+                    on:input=move |ev| set_url.set(event_target_value(&ev))/>
+                <label for="race-name">"Name"</label>
+                <input
+                    id="race-name"
+                    maxlength="32"
+                    prop:value=move || name.get()
+                    disabled=move || connected.get()
+                    on:input=move |ev| set_name.set(event_target_value(&ev))/>
+                <Show
+                    when=move || connected.get()
+                    fallback=move || view! {
+                        <button type="button" on:click=move |_| join(url.get_untracked())>
+                            "Join"
+                        </button>
+                    }>
+                    <button type="button" on:click=move |_| leave()>"Leave"</button>
+                </Show>
+            </div>
+            <Show when=move || connected.get()>
+                <div class="row race-start">
+                    <label for="race-length">"Characters"</label>
+                    <input
+                        id="race-length"
+                        type="number"
+                        min="1"
+                        max=MAX_LENGTH
+                        prop:value=move || length.get()
+                        on:input=move |ev| set_length.set(event_target_value(&ev))/>
+                    <button type="button" disabled=under_way on:click=send_start>
+                        "Start race"
+                    </button>
+                </div>
+                <ul class="race-players">
+                    <For each=move || players.get() key=|player| player.id let:player>
+                        {
+                            let id = player.id;
+                            let player = move || {
+                                players.get().into_iter().find(|p| p.id == id)
+                            };
+                            view! {
+                                <li class:race-me=move || my_id.get() == Some(id)>
+                                    <span class="race-name">
+                                        {move || player().map(|p| p.name).unwrap_or_default()}
+                                    </span>
+                                    <progress
+                                        max=move || race_length.get().max(1)
+                                        value=move || player().map(|p| p.position).unwrap_or_default()
+                                        aria-label=move || player().map(|p| p.name).unwrap_or_default()>
+                                    </progress>
+                                    <span class="race-status">
+                                        {move || match player() {
+                                            Some(p) if !p.racing => "watching".to_string(),
+                                            Some(Player { finished_ms: Some(ms), .. }) => seconds(ms),
+                                            Some(p) => format!("{} / {}", p.position, race_length.get()),
+                                            None => String::new(),
+                                        }}
+                                    </span>
+                                </li>
+                            }
+                        }
+                    </For>
+                </ul>
+            </Show>
+            <Show when=racing>
+                <p class="race-countdown" role="status">
+                    {move || match countdown.get() {
+                        0 if finished() && standings.get().is_none() => {
+                            "Finished! Waiting for the others\u{2026}".to_string()
+                        }
+                        0 => "Go!".to_string(),
+                        secs => format!("Starting in {}\u{2026}", secs),
+                    }}
+                </p>
+                <p class="race-sequence" aria-hidden="true">
+                    <span class="race-done">
+                        {move || shown(sequence.get().get(..position.get()).unwrap_or_default())}
+                    </span>
+                    <span class="race-current">
+                        {move || sequence.get().get(position.get()).map(|c| glyph(*c).to_string())}
+                    </span>
+                    <span>
+                        {move || {
+                            let sequence = sequence.get();
+                            shown(sequence.get(position.get() + 1..).unwrap_or_default())
+                        }}
+                    </span>
+                </p>
+                <input
+                    node_ref=input_ref
+                    id="race-input"
+                    aria-label="Race input"
+                    autocomplete="off"
+                    readonly=move || {
+                        countdown.get() > 0 || finished() || standings.get().is_some()
+                    }
+                    on:input=move |ev: Event| {
+                        // A dead key starts a composition; wait for the composed character.
+                        if !ev.dyn_ref::<InputEvent>().is_some_and(|ev| ev.is_composing()) {
+                            type_char();
+                        }
+                    }
+                    on:compositionend=move |_| type_char()
+                />
+                <span class="hint">{move || format!("Misses: {}", misses.get())}</span>
+            </Show>
+            <Show when=move || standings.get().is_some()>
+                <p class="race-winner" role="status">
+                    {move || {
+                        let standings = standings.get().unwrap_or_default();
+                        match standings.first().filter(|p| p.finished_ms.is_some()) {
+                            Some(winner) => format!(
+                                "{} wins in {} with {:.1}% accuracy!",
+                                winner.name,
+                                seconds(winner.finished_ms.unwrap_or_default()),
+                                winner.accuracy()
+                            ),
+                            None => "Nobody finished the race.".to_string(),
+                        }
+                    }}
+                </p>
+                <table class="race-standings">
+                    <tr><th>"Place"</th><th>"Name"</th><th>"Time"</th><th>"Accuracy"</th></tr>
+                    {move || {
+                        standings
+                            .get()
+                            .unwrap_or_default()
+                            .into_iter()
+                            .enumerate()
+                            .map(|(place, player)| view! {
+                                <tr class:race-me=move || my_id.get() == Some(player.id)>
+                                    <td>{place + 1}</td>
+                                    <td>{player.name.clone()}</td>
+                                    <td>
+                                        {player.finished_ms.map_or("did not finish".to_string(), seconds)}
+                                    </td>
+                                    <td>{format!("{:.1}%", player.accuracy())}</td>
+                                </tr>
+                            })
+                            .collect_view()
+                    }}
+                </table>
+            </Show>
+        </div>
+    }
+}
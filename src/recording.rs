@@ -0,0 +1,166 @@
+use crate::layout::Layout;
+use crate::target_char::TargetChar;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+// Ids are start times to the millisecond, like "20250301-140509-123", so
+// quick restarts don't overwrite each other.
+const ID_FORMAT: &str = "%Y%m%d-%H%M%S-%3f";
+
+/// Something that happened during a recorded session, `at_ms` after it
+/// started.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum KeyEvent {
+    /// A new character was put in front of the user
+    Target { at_ms: u64, target: TargetChar },
+    /// `code` is the physical key, `key` what the layout made of it
+    KeyDown { at_ms: u64, key: String, code: String },
+    KeyUp { at_ms: u64, key: String, code: String },
+    /// A character was typed and judged against the target
+    Typed {
+        at_ms: u64,
+        typed: char,
+        target: TargetChar,
+        hit: bool,
+    },
+}
+
+impl KeyEvent {
+    pub fn at_ms(&self) -> u64 {
+        match self {
+            KeyEvent::Target { at_ms, .. }
+            | KeyEvent::KeyDown { at_ms, .. }
+            | KeyEvent::KeyUp { at_ms, .. }
+            | KeyEvent::Typed { at_ms, .. } => *at_ms,
+        }
+    }
+}
+
+/// The full keystroke log of one practice run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    pub started: NaiveDateTime,
+    /// Personal best key of the drill, e.g. "letters-qwerty"
+    pub mode: String,
+    pub layout: Layout,
+    /// What the drill's characters were drawn with; runs recorded before
+    /// seeding have none and can't be raced as a ghost
+    #[serde(default)]
+    pub seed: Option<u64>,
+    pub events: Vec<KeyEvent>,
+    // Clock reading at `started`, to time events against.
+    #[serde(skip)]
+    start_ms: u64,
+    // How many events have been saved; later saves only send the rest.
+    #[serde(skip)]
+    saved: usize,
+}
+
+/// Everything about a recording but its events, saved ahead of them.
+#[derive(Debug, Serialize)]
+pub struct RecordingHeader<'a> {
+    pub started: NaiveDateTime,
+    pub mode: &'a str,
+    pub layout: Layout,
+    pub seed: Option<u64>,
+}
+
+impl Recording {
+    pub fn new(
+        mode: String,
+        layout: Layout,
+        seed: u64,
+        started: NaiveDateTime,
+        start_ms: u64,
+    ) -> Self {
+        Recording {
+            started,
+            mode,
+            layout,
+            seed: Some(seed),
+            events: Vec::new(),
+            start_ms,
+            saved: 0,
+        }
+    }
+
+    /// Saved recordings are named after the time they started.
+    pub fn id(&self) -> String {
+        self.started.format(ID_FORMAT).to_string()
+    }
+
+    /// Milliseconds since the recording started, for stamping a new event.
+    pub fn elapsed_ms(&self, now_ms: u64) -> u64 {
+        now_ms.saturating_sub(self.start_ms)
+    }
+
+    pub fn push(&mut self, event: KeyEvent) {
+        self.events.push(event);
+    }
+
+    /// Whether anything was actually typed; empty runs aren't worth saving.
+    pub fn has_attempts(&self) -> bool {
+        self.events
+            .iter()
+            .any(|event| matches!(event, KeyEvent::Typed { .. }))
+    }
+
+    /// What to save next: the header, if nothing has been saved yet, and
+    /// the events since the last save. They count as saved from here on.
+    pub fn take_unsaved(&mut self) -> (Option<RecordingHeader<'_>>, &[KeyEvent]) {
+        let from = self.saved.min(self.events.len());
+        self.saved = self.events.len();
+        let header = (from == 0).then_some(RecordingHeader {
+            started: self.started,
+            mode: &self.mode,
+            layout: self.layout,
+            seed: self.seed,
+        });
+        (header, &self.events[from..])
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        self.events.last().map_or(0, KeyEvent::at_ms)
+    }
+}
+
+/// Label for a saved recording id, e.g. "2025-03-01 14:05:09".
+pub fn describe_id(id: &str) -> String {
+    NaiveDateTime::parse_from_str(id, ID_FORMAT)
+        .map(|started| started.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|_| id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(at_ms: u64) -> KeyEvent {
+        KeyEvent::Target {
+            at_ms,
+            target: TargetChar::new('a').unwrap(),
+        }
+    }
+
+    #[test]
+    fn saves_send_only_what_is_new() {
+        let started = NaiveDateTime::parse_from_str("20250301-140509-123", ID_FORMAT).unwrap();
+        let mut recording = Recording::new("mode".to_string(), Layout::Qwerty, 7, started, 0);
+        recording.push(target(1));
+        recording.push(target(2));
+
+        let (header, events) = recording.take_unsaved();
+        assert_eq!(header.map(|h| (h.mode, h.seed)), Some(("mode", Some(7))));
+        assert_eq!(events, [target(1), target(2)]);
+
+        let (header, events) = recording.take_unsaved();
+        assert!(header.is_none() && events.is_empty());
+
+        recording.push(target(3));
+        let (header, events) = recording.take_unsaved();
+        assert!(header.is_none());
+        assert_eq!(events, [target(3)]);
+        assert_eq!(recording.id(), "20250301-140509-123");
+    }
+}
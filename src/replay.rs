@@ -0,0 +1,255 @@
+use crate::backend;
+use crate::common::glyph;
+use crate::recording::{describe_id, KeyEvent, Recording};
+use crate::svg_keyboard::SvgKeyboard;
+use crate::target_char::TargetChar;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos::{ev::Event, html};
+use std::time::Duration;
+
+// How often playback advances, in real milliseconds.
+const TICK_MS: u64 = 30;
+const SPEEDS: [u64; 4] = [1, 2, 4, 8];
+// The replay's keyboards share the page with the practice ones.
+const ID_PREFIX: &str = "replay-";
+
+/// A typed character as replayed: what was typed, whether it was right and
+/// how long after the target appeared.
+#[derive(Debug, Clone, PartialEq)]
+struct Attempt {
+    typed: char,
+    hit: bool,
+    reaction_ms: u64,
+}
+
+#[component]
+pub fn ReplayViewer(profile: ReadSignal<String>) -> impl IntoView {
+    let (ids, set_ids) = signal(Vec::<String>::new());
+    let (recording, set_recording) = signal(None::<Recording>);
+    let (speed, set_speed) = signal(1u64);
+    let (position, set_position) = signal(0u64);
+    let (playing, set_playing) = signal(false);
+    let (target, set_target) = signal(None::<TargetChar>);
+    let (attempts, set_attempts) = signal(Vec::<Attempt>::new());
+    // Index of the next event to play, and when the current target appeared.
+    let cursor = StoredValue::new(0usize);
+    let target_at = StoredValue::new(0u64);
+    let interval = StoredValue::new(None::<IntervalHandle>);
+    let keyboard_ref = NodeRef::<html::Div>::new();
+
+    Effect::new(move |_| {
+        let name = profile.get();
+        spawn_local(async move {
+            set_ids.set(backend::list_recordings(&name).await.unwrap_or_default());
+        });
+    });
+
+    let set_key_class = move |id: &str, class: &str, on: bool| {
+        let Some(keyboard) = keyboard_ref.get_untracked() else {
+            return;
+        };
+        if let Some(key) = SvgKeyboard::find_key(&keyboard, id) {
+            let _ = if on {
+                key.class_list().add_1(class)
+            } else {
+                key.class_list().remove_1(class)
+            };
+        }
+    };
+
+    let remove_class = move |class: &str| {
+        let Some(keyboard) = keyboard_ref.get_untracked() else {
+            return;
+        };
+        // The collection is live, so it shrinks as classes come off.
+        let keys = keyboard.get_elements_by_class_name(class);
+        while let Some(key) = keys.item(0) {
+            let _ = key.class_list().remove_1(class);
+        }
+    };
+
+    let clear_keys = move || {
+        remove_class("key-highlight");
+        remove_class("key-pressed");
+    };
+
+    let apply = move |event: &KeyEvent, recording: &Recording| match event {
+        KeyEvent::Target { at_ms, target } => {
+            remove_class("key-highlight");
+            for id in SvgKeyboard::get_css_ids(target, recording.layout) {
+                set_key_class(id, "key-highlight", true);
+            }
+            set_target.set(Some(*target));
+            target_at.set_value(*at_ms);
+        }
+        KeyEvent::KeyDown { code, .. } => {
+            if let Some(id) = SvgKeyboard::key_for_code(code) {
+                set_key_class(id, "key-pressed", true);
+            }
+        }
+        KeyEvent::KeyUp { code, .. } => {
+            if let Some(id) = SvgKeyboard::key_for_code(code) {
+                set_key_class(id, "key-pressed", false);
+            }
+        }
+        KeyEvent::Typed {
+            at_ms, typed, hit, ..
+        } => {
+            let attempt = Attempt {
+                typed: *typed,
+                hit: *hit,
+                reaction_ms: at_ms.saturating_sub(target_at.get_value()),
+            };
+            set_attempts.update(|list| list.push(attempt));
+        }
+    };
+
+    let stop = move || {
+        if let Some(handle) = interval.get_value() {
+            handle.clear();
+        }
+        interval.set_value(None);
+        set_playing.set(false);
+    };
+    on_cleanup(stop);
+
+    let rewind = move || {
+        clear_keys();
+        cursor.set_value(0);
+        target_at.set_value(0);
+        set_position.set(0);
+        set_target.set(None);
+        set_attempts.set(Vec::new());
+    };
+
+    let tick = move || {
+        let pos = position.get_untracked() + TICK_MS * speed.get_untracked();
+        set_position.set(pos);
+        let finished = recording.with_untracked(|recording| {
+            let Some(recording) = recording else {
+                return true;
+            };
+            let mut next = cursor.get_value();
+            while let Some(event) = recording.events.get(next).filter(|e| e.at_ms() <= pos) {
+                apply(event, recording);
+                next += 1;
+            }
+            cursor.set_value(next);
+            next >= recording.events.len()
+        });
+        if finished {
+            stop();
+        }
+    };
+
+    let toggle_play = move |_| {
+        if playing.get_untracked() {
+            stop();
+            return;
+        }
+        let at_end = recording.with_untracked(|recording| {
+            recording
+                .as_ref()
+                .is_some_and(|r| cursor.get_value() >= r.events.len())
+        });
+        if at_end {
+            rewind();
+        }
+        if let Ok(handle) = set_interval_with_handle(tick, Duration::from_millis(TICK_MS)) {
+            interval.set_value(Some(handle));
+            set_playing.set(true);
+        }
+    };
+
+    let select_recording = move |ev: Event| {
+        let id = event_target_value(&ev);
+        let name = profile.get_untracked();
+        stop();
+        spawn_local(async move {
+            let loaded = backend::load_recording(&name, &id).await.ok().flatten();
+            if let (Some(keyboard), Some(recording)) = (keyboard_ref.get_untracked(), &loaded) {
+                SvgKeyboard::relabel(&keyboard, recording.layout);
+            }
+            set_recording.set(loaded);
+            rewind();
+        });
+    };
+
+    let seconds = |ms: u64| format!("{:.1} s", ms as f64 / 1000.0);
+
+    view! {
+        <div id="replay-panel">
+            <div class="replay-controls">
+                <select on:change=select_recording>
+                    <option value="" selected disabled>"Choose a session..."</option>
+                    <For each=move || ids.get() key=|id| id.clone() let:id>
+                        <option value=id.clone()>{describe_id(&id)}</option>
+                    </For>
+                </select>
+                <select on:change=move |ev| {
+                    if let Ok(s) = event_target_value(&ev).parse::<u64>() {
+                        set_speed.set(s);
+                    }
+                }>
+                    {SPEEDS
+                        .into_iter()
+                        .map(|s| {
+                            view! {
+                                <option value=s selected=move || speed.get() == s>
+                                    {format!("{}\u{00D7}", s)}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+                <button
+                    type="button"
+                    disabled=move || recording.with(Option::is_none)
+                    on:click=toggle_play>
+                    {move || if playing.get() { "Pause" } else { "Play" }}
+                </button>
+                <span class="hint">
+                    {move || {
+                        let duration = recording.with(|r| r.as_ref().map_or(0, Recording::duration_ms));
+                        format!("{} / {}", seconds(position.get().min(duration)), seconds(duration))
+                    }}
+                </span>
+            </div>
+            <p class="replay-target">
+                {move || target.get().map(|t| t.label()).unwrap_or_default()}
+            </p>
+            <div class="keyboards replay-keyboards" node_ref=keyboard_ref data-id-prefix=ID_PREFIX>
+                <div inner_html=SvgKeyboard::with_prefix(SvgKeyboard::render(), ID_PREFIX)></div>
+                <div
+                    inner_html=SvgKeyboard::with_prefix(SvgKeyboard::render_numpad(), ID_PREFIX)
+                    hidden=move || recording.with(|r| r.as_ref().is_none_or(|r| r.mode != "numpad"))></div>
+            </div>
+            <p class="replay-attempts">
+                {move || {
+                    attempts
+                        .get()
+                        .into_iter()
+                        .map(|attempt| {
+                            view! {
+                                <span
+                                    class:replay-hit=attempt.hit
+                                    class:replay-miss=!attempt.hit
+                                    title=format!("{} ms", attempt.reaction_ms)>
+                                    {glyph(attempt.typed).to_string()}
+                                </span>
+                            }
+                        })
+                        .collect_view()
+                }}
+            </p>
+            <p class="hint">
+                {move || {
+                    let attempts = attempts.get();
+                    let hits = attempts.iter().filter(|a| a.hit).count();
+                    format!("Hits: {}  Misses: {}", hits, attempts.len() - hits)
+                }}
+            </p>
+        </div>
+    }
+}
@@ -0,0 +1,487 @@
+use crate::common::crc32;
+use crate::history::DayTotal;
+use crate::layout::Layout;
+use crate::snippets::Language;
+use crate::target_char::TargetChar;
+use leptos::web_sys::HtmlElement;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use wasm_bindgen::JsCast;
+
+pub const MIN_FONT_SIZE: u32 = 24;
+pub const MAX_FONT_SIZE: u32 = 96;
+pub const MAX_LOOK_AHEAD: usize = 10;
+
+/// The colours a theme sets, as CSS variables (`--background` and so on)
+/// with the label they're edited under.
+pub const THEME_COLORS: [(&str, &str); 15] = [
+    ("background", "Background"),
+    ("text", "Text"),
+    ("surface", "Panels"),
+    ("control", "Buttons and inputs"),
+    ("control-text", "Button text"),
+    ("accent", "Accent"),
+    ("key-top", "Key top"),
+    ("key-bottom", "Key bottom"),
+    ("key-border", "Key outline"),
+    ("key-text", "Key labels"),
+    ("highlight", "Target key"),
+    ("highlight-border", "Target key outline"),
+    ("next-key", "Next key"),
+    ("hit", "Hits"),
+    ("miss", "Misses"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CharSet {
+    All,
+    Letters,
+    Lowercase,
+    Digits,
+    Symbols,
+    Alphanumeric,
+    /// Letters and symbols beyond ASCII that the layout can type
+    Accented,
+}
+
+impl CharSet {
+    pub const ALL: [CharSet; 7] = [
+        CharSet::All,
+        CharSet::Letters,
+        CharSet::Lowercase,
+        CharSet::Digits,
+        CharSet::Symbols,
+        CharSet::Alphanumeric,
+        CharSet::Accented,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            CharSet::All => "ascii",
+            CharSet::Letters => "letters",
+            CharSet::Lowercase => "lowercase",
+            CharSet::Digits => "digits",
+            CharSet::Symbols => "symbols",
+            CharSet::Alphanumeric => "alphanumeric",
+            CharSet::Accented => "accented",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CharSet::All => "All keys",
+            CharSet::Letters => "Letters",
+            CharSet::Lowercase => "Lowercase letters",
+            CharSet::Digits => "Digits",
+            CharSet::Symbols => "Symbols",
+            CharSet::Alphanumeric => "Letters and digits",
+            CharSet::Accented => "Accented and other non-ASCII",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        CharSet::ALL.into_iter().find(|item| item.id() == id)
+    }
+
+    pub fn contains(&self, target: &TargetChar) -> bool {
+        let c = target.as_char();
+        match self {
+            CharSet::All => c.is_ascii(),
+            CharSet::Letters => c.is_alphabetic(),
+            CharSet::Lowercase => c.is_lowercase(),
+            CharSet::Digits => c.is_ascii_digit(),
+            CharSet::Symbols => c.is_ascii_punctuation(),
+            CharSet::Alphanumeric => c.is_alphabetic() || c.is_ascii_digit(),
+            CharSet::Accented => !c.is_ascii(),
+        }
+    }
+
+    /// The characters of this set that `layout` can type.
+    pub fn pool(&self, layout: Layout) -> Vec<TargetChar> {
+        layout
+            .chars()
+            .into_iter()
+            .filter(|target| self.contains(target))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DrillMode {
+    /// Random characters from the selected character set
+    Random,
+    /// Code snippets, typed line by line
+    Code,
+    /// Passages from a local text file or folder
+    Text,
+    /// Digits and operators on the numeric keypad
+    Numpad,
+    /// The lesson course, one set of keys at a time
+    Lesson,
+    /// Symbols prompted by name ("caret") rather than shown
+    Names,
+}
+
+impl DrillMode {
+    pub const ALL: [DrillMode; 6] = [
+        DrillMode::Random,
+        DrillMode::Lesson,
+        DrillMode::Names,
+        DrillMode::Code,
+        DrillMode::Text,
+        DrillMode::Numpad,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            DrillMode::Random => "random",
+            DrillMode::Code => "code",
+            DrillMode::Text => "text",
+            DrillMode::Numpad => "numpad",
+            DrillMode::Lesson => "lesson",
+            DrillMode::Names => "names",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DrillMode::Random => "Random characters",
+            DrillMode::Code => "Code snippets",
+            DrillMode::Text => "Text from files",
+            DrillMode::Numpad => "Numeric keypad",
+            DrillMode::Lesson => "Lessons",
+            DrillMode::Names => "Symbol names",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        DrillMode::ALL.into_iter().find(|item| item.id() == id)
+    }
+}
+
+/// What happens when the wrong key is pressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorPolicy {
+    /// Every wrong key is a miss and the target stays until it's typed; the
+    /// hit is timed from the last key pressed
+    Stop,
+    /// A wrong key is a miss and the drill moves on; only hits are timed
+    Lenient,
+    /// A wrong key is a miss and stays typed until deleted with Backspace;
+    /// the hit is timed from when the target appeared, correction included
+    Correct,
+}
+
+impl ErrorPolicy {
+    pub const ALL: [ErrorPolicy; 3] = [ErrorPolicy::Stop, ErrorPolicy::Lenient, ErrorPolicy::Correct];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            ErrorPolicy::Stop => "stop",
+            ErrorPolicy::Lenient => "lenient",
+            ErrorPolicy::Correct => "correct",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ErrorPolicy::Stop => "Retry until right",
+            ErrorPolicy::Lenient => "Mark and move on",
+            ErrorPolicy::Correct => "Backspace to correct",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        ErrorPolicy::ALL.into_iter().find(|item| item.id() == id)
+    }
+}
+
+/// What a daily goal is counted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GoalKind {
+    Characters,
+    Minutes,
+}
+
+impl GoalKind {
+    pub const ALL: [GoalKind; 2] = [GoalKind::Characters, GoalKind::Minutes];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            GoalKind::Characters => "characters",
+            GoalKind::Minutes => "minutes",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GoalKind::Characters => "characters",
+            GoalKind::Minutes => "minutes",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        GoalKind::ALL.into_iter().find(|item| item.id() == id)
+    }
+
+    /// How much of a goal of this kind `total` counts for.
+    pub fn measure(&self, total: &DayTotal) -> u64 {
+        match self {
+            GoalKind::Characters => total.characters as u64,
+            GoalKind::Minutes => total.practice_ms / 60_000,
+        }
+    }
+}
+
+/// Which past run to race as a ghost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GhostKind {
+    Off,
+    /// The run that set the fastest average for the mode
+    Best,
+    /// The most recent run in the mode
+    Last,
+}
+
+impl GhostKind {
+    pub const ALL: [GhostKind; 3] = [GhostKind::Off, GhostKind::Best, GhostKind::Last];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            GhostKind::Off => "off",
+            GhostKind::Best => "best",
+            GhostKind::Last => "last",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GhostKind::Off => "Off",
+            GhostKind::Best => "Personal best",
+            GhostKind::Last => "Last session",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        GhostKind::ALL.into_iter().find(|item| item.id() == id)
+    }
+}
+
+/// The built-in themes; their colours are in `styles.css`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    /// Light or dark, whichever the system prefers
+    System,
+    Light,
+    Dark,
+    HighContrast,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 4] = [Theme::System, Theme::Light, Theme::Dark, Theme::HighContrast];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Theme::System => "system",
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::HighContrast => "high-contrast",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Theme::System => "Follow system",
+            Theme::Light => "Light",
+            Theme::Dark => "Dark",
+            Theme::HighContrast => "High contrast",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Theme::ALL.into_iter().find(|item| item.id() == id)
+    }
+}
+
+/// A theme of the user's own: a built-in one with some colours replaced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomTheme {
+    pub name: String,
+    /// The theme it starts from
+    pub base: Theme,
+    /// CSS colours by `THEME_COLORS` name; the rest come from `base`
+    pub colors: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub keyboard_visible: bool,
+    /// Colour the keyboard by finger and say which finger to use
+    pub finger_guide: bool,
+    /// Show the numeric keypad next to the keyboard outside the numpad drill
+    pub numpad_visible: bool,
+    pub drill_mode: DrillMode,
+    pub code_language: Language,
+    /// Skip the indentation at the start of each code line instead of typing it
+    pub skip_indent: bool,
+    /// File or folder the text drill reads from
+    pub text_source: Option<String>,
+    /// Lesson being practised, or the next one to pass if unset
+    pub lesson: Option<String>,
+    pub layout: Layout,
+    pub char_set: CharSet,
+    pub error_policy: ErrorPolicy,
+    pub theme: Theme,
+    pub custom_themes: Vec<CustomTheme>,
+    /// Name of the custom theme in use, which takes the place of `theme`
+    pub custom_theme: Option<String>,
+    /// Hits, misses and the heatmap in colours told apart with any colour vision
+    pub colour_blind: bool,
+    /// Font size of the prompt character in pixels
+    pub font_size: u32,
+    /// Master switch for every sound below
+    pub sound_enabled: bool,
+    /// 0-100
+    pub sound_volume: u32,
+    /// Click on a hit
+    pub hit_sound: bool,
+    /// Tone on a miss
+    pub miss_sound: bool,
+    /// Chime on a new record or a passed lesson
+    pub chime_sound: bool,
+    /// Seconds without input before a reaction time is treated as idle, 0 to disable
+    pub idle_timeout_secs: u32,
+    /// Characters per test, 0 for an endless run
+    pub test_length: usize,
+    /// Upcoming characters shown after the current one, 0 to show none
+    pub look_ahead: usize,
+    /// Past run raced alongside this one, on the same characters
+    pub ghost: GhostKind,
+    /// Practice to do each day, in `goal_kind` units; 0 for no goal
+    pub daily_goal: u32,
+    pub goal_kind: GoalKind,
+    /// Hour of the day (0-23) to be reminded if the goal isn't met yet
+    pub reminder_hour: Option<u32>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            keyboard_visible: false,
+            numpad_visible: false,
+            finger_guide: false,
+            drill_mode: DrillMode::Random,
+            code_language: Language::Rust,
+            skip_indent: false,
+            text_source: None,
+            lesson: None,
+            layout: Layout::Qwerty,
+            char_set: CharSet::All,
+            error_policy: ErrorPolicy::Stop,
+            theme: Theme::System,
+            custom_themes: Vec::new(),
+            custom_theme: None,
+            colour_blind: false,
+            font_size: 48,
+            sound_enabled: false,
+            sound_volume: 50,
+            hit_sound: true,
+            miss_sound: true,
+            chime_sound: true,
+            idle_timeout_secs: 10,
+            test_length: 0,
+            look_ahead: 0,
+            ghost: GhostKind::Off,
+            daily_goal: 0,
+            goal_kind: GoalKind::Minutes,
+            reminder_hour: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Key the personal bests are kept against.
+    pub fn mode_key(&self) -> String {
+        match self.drill_mode {
+            DrillMode::Random => format!("{}-{}", self.char_set.id(), self.layout.id()),
+            // Skipping indentation leaves fewer keys to type, so it's kept apart.
+            DrillMode::Code => format!(
+                "code_{}{}-{}",
+                self.code_language.id(),
+                if self.skip_indent { "_noindent" } else { "" },
+                self.layout.id()
+            ),
+            // Passages from elsewhere are kept apart by a hash of where
+            // they came from, since the path itself can be anything.
+            DrillMode::Text => match &self.text_source {
+                Some(source) => format!("text_{:08x}-{}", crc32(source.as_bytes()), self.layout.id()),
+                None => format!("text-{}", self.layout.id()),
+            },
+            DrillMode::Names => format!("names-{}", self.layout.id()),
+            // The keypad is the same whatever the layout.
+            DrillMode::Numpad => "numpad".to_string(),
+            DrillMode::Lesson => format!(
+                "lesson_{}-{}",
+                self.lesson.as_deref().unwrap_or_default(),
+                self.layout.id()
+            ),
+        }
+    }
+
+    pub fn idle_timeout_ms(&self) -> Option<u64> {
+        match self.idle_timeout_secs {
+            0 => None,
+            secs => Some(secs as u64 * 1000),
+        }
+    }
+
+    /// Progress towards the daily goal as (done, goal), if there is one.
+    pub fn goal_progress(&self, today: &DayTotal) -> Option<(u64, u64)> {
+        match self.daily_goal {
+            0 => None,
+            goal => Some((self.goal_kind.measure(today), goal as u64)),
+        }
+    }
+
+    pub fn active_custom_theme(&self) -> Option<&CustomTheme> {
+        let name = self.custom_theme.as_deref()?;
+        self.custom_themes.iter().find(|theme| theme.name == name)
+    }
+
+    pub fn active_custom_theme_mut(&mut self) -> Option<&mut CustomTheme> {
+        let name = self.custom_theme.as_deref()?;
+        self.custom_themes.iter_mut().find(|theme| theme.name == name)
+    }
+
+    pub fn apply(&self) {
+        // Theme and font size live on the document so they take effect before
+        // (and independently of) any component rendering.
+        let Some(root) = leptos::prelude::document().document_element() else {
+            return;
+        };
+        let custom = self.active_custom_theme();
+        let base = custom.map_or(self.theme, |theme| theme.base);
+        let _ = root.set_attribute("data-theme", base.id());
+        let _ = if self.colour_blind {
+            root.set_attribute("data-palette", "colour-blind")
+        } else {
+            root.remove_attribute("data-palette")
+        };
+        let style = root.unchecked_into::<HtmlElement>().style();
+        // A custom theme's colours go on the root element, over the base's.
+        for (name, _) in THEME_COLORS {
+            let property = format!("--{}", name);
+            match custom.and_then(|theme| theme.colors.get(name)) {
+                Some(color) => {
+                    let _ = style.set_property(&property, color);
+                }
+                None => {
+                    let _ = style.remove_property(&property);
+                }
+            }
+        }
+        let font_size = self.font_size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE);
+        let _ = style.set_property("--prompt-font-size", &format!("{}px", font_size));
+    }
+}
@@ -0,0 +1,585 @@
+use crate::backend;
+use crate::layout::Layout;
+use crate::settings::{
+    CharSet, CustomTheme, DrillMode, ErrorPolicy, GhostKind, GoalKind, Settings, Theme,
+    MAX_FONT_SIZE, MAX_LOOK_AHEAD, MIN_FONT_SIZE, THEME_COLORS,
+};
+use crate::snippets::Language;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+// Custom themes are listed in the theme menu after the built-in ones.
+const CUSTOM_PREFIX: &str = "custom:";
+
+// `<input type="color">` only takes "#rrggbb".
+fn picker_color(color: &str) -> String {
+    let hex = color.trim().strip_prefix('#').unwrap_or_default();
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return "#000000".to_string();
+    }
+    match hex.len() {
+        3 | 4 => hex.chars().take(3).fold("#".to_string(), |mut out, c| {
+            out.push(c);
+            out.push(c);
+            out
+        }),
+        6 | 8 => format!("#{}", &hex[..6]),
+        _ => "#000000".to_string(),
+    }
+}
+
+// The colour the page is using for a theme variable right now.
+fn current_color(name: &str) -> String {
+    let color = document().document_element().and_then(|root| {
+        window()
+            .get_computed_style(&root)
+            .ok()
+            .flatten()?
+            .get_property_value(&format!("--{}", name))
+            .ok()
+    });
+    picker_color(&color.unwrap_or_default())
+}
+
+#[component]
+pub fn SettingsPanel(settings: ReadSignal<Settings>, on_change: Callback<Settings>) -> impl IntoView {
+    // Every control edits a copy of the current settings and hands it back.
+    let update = move |f: &dyn Fn(&mut Settings)| {
+        let mut new_settings = settings.get_untracked();
+        f(&mut new_settings);
+        on_change.run(new_settings);
+    };
+
+    let pick_source = move |directory: bool| {
+        spawn_local(async move {
+            if let Ok(Some(path)) = backend::pick_practice_source(directory).await {
+                update(&|s| s.text_source = Some(path.clone()));
+            }
+        });
+    };
+
+    let select_theme = move |value: String| match value.strip_prefix(CUSTOM_PREFIX) {
+        Some(name) => update(&|s| s.custom_theme = Some(name.to_string())),
+        None => {
+            if let Some(theme) = Theme::from_id(&value) {
+                update(&|s| {
+                    s.theme = theme;
+                    s.custom_theme = None;
+                });
+            }
+        }
+    };
+
+    // Starts a custom theme from the one in use.
+    let new_theme = move || {
+        update(&|s| {
+            let mut number = s.custom_themes.len() + 1;
+            while s.custom_themes.iter().any(|theme| theme.name == format!("Custom {}", number)) {
+                number += 1;
+            }
+            let current = s.active_custom_theme();
+            let theme = CustomTheme {
+                name: format!("Custom {}", number),
+                base: current.map_or(s.theme, |theme| theme.base),
+                colors: current.map(|theme| theme.colors.clone()).unwrap_or_default(),
+            };
+            s.custom_theme = Some(theme.name.clone());
+            s.custom_themes.push(theme);
+        });
+    };
+
+    let rename_theme = move |name: String| {
+        let name = name.trim().to_string();
+        update(&|s| {
+            if name.is_empty() || s.custom_themes.iter().any(|theme| theme.name == name) {
+                return;
+            }
+            let Some(theme) = s.active_custom_theme_mut() else {
+                return;
+            };
+            theme.name = name.clone();
+            s.custom_theme = Some(name.clone());
+        });
+    };
+
+    let delete_theme = move || {
+        update(&|s| {
+            if let Some(name) = s.custom_theme.take() {
+                s.custom_themes.retain(|theme| theme.name != name);
+            }
+        });
+    };
+
+    view! {
+        <div id="settings-panel">
+            <label>
+                "Mode"
+                <select on:change=move |ev| {
+                    if let Some(drill_mode) = DrillMode::from_id(&event_target_value(&ev)) {
+                        update(&|s| s.drill_mode = drill_mode);
+                    }
+                }>
+                    {DrillMode::ALL
+                        .into_iter()
+                        .map(|drill_mode| {
+                            view! {
+                                <option
+                                    value=drill_mode.id()
+                                    selected=move || settings.get().drill_mode == drill_mode>
+                                    {drill_mode.label()}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+            </label>
+            <label>
+                "Code language"
+                <select
+                    disabled=move || settings.get().drill_mode != DrillMode::Code
+                    on:change=move |ev| {
+                        if let Some(language) = Language::from_id(&event_target_value(&ev)) {
+                            update(&|s| s.code_language = language);
+                        }
+                    }>
+                    {Language::ALL
+                        .into_iter()
+                        .map(|language| {
+                            view! {
+                                <option
+                                    value=language.id()
+                                    selected=move || settings.get().code_language == language>
+                                    {language.label()}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+            </label>
+            <label>
+                "Skip indentation"
+                <input
+                    type="checkbox"
+                    disabled=move || settings.get().drill_mode != DrillMode::Code
+                    prop:checked=move || settings.get().skip_indent
+                    on:change=move |ev| {
+                        let enabled = event_target_checked(&ev);
+                        update(&|s| s.skip_indent = enabled);
+                    }
+                />
+            </label>
+            <div class="settings-row">
+                "Practice text"
+                <span class="text-source">
+                    {move || settings.get().text_source.unwrap_or_else(|| "none".to_string())}
+                </span>
+                <button type="button" on:click=move |_| pick_source(false)>"File..."</button>
+                <button type="button" on:click=move |_| pick_source(true)>"Folder..."</button>
+            </div>
+            <label>
+                "Layout"
+                <select on:change=move |ev| {
+                    if let Some(layout) = Layout::from_id(&event_target_value(&ev)) {
+                        update(&|s| s.layout = layout);
+                    }
+                }>
+                    {Layout::ALL
+                        .into_iter()
+                        .map(|layout| {
+                            view! {
+                                <option
+                                    value=layout.id()
+                                    selected=move || settings.get().layout == layout>
+                                    {layout.label()}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+            </label>
+            <label>
+                "Characters"
+                <select on:change=move |ev| {
+                    if let Some(char_set) = CharSet::from_id(&event_target_value(&ev)) {
+                        update(&|s| s.char_set = char_set);
+                    }
+                }>
+                    {CharSet::ALL
+                        .into_iter()
+                        .map(|char_set| {
+                            view! {
+                                <option
+                                    value=char_set.id()
+                                    selected=move || settings.get().char_set == char_set>
+                                    {char_set.label()}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+            </label>
+            <label>
+                "On error"
+                <select on:change=move |ev| {
+                    if let Some(error_policy) = ErrorPolicy::from_id(&event_target_value(&ev)) {
+                        update(&|s| s.error_policy = error_policy);
+                    }
+                }>
+                    {ErrorPolicy::ALL
+                        .into_iter()
+                        .map(|error_policy| {
+                            view! {
+                                <option
+                                    value=error_policy.id()
+                                    selected=move || settings.get().error_policy == error_policy>
+                                    {error_policy.label()}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+            </label>
+            <label>
+                "Theme"
+                <select on:change=move |ev| select_theme(event_target_value(&ev))>
+                    {Theme::ALL
+                        .into_iter()
+                        .map(|theme| {
+                            view! {
+                                <option
+                                    value=theme.id()
+                                    selected=move || {
+                                        let settings = settings.get();
+                                        settings.custom_theme.is_none() && settings.theme == theme
+                                    }>
+                                    {theme.label()}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                    {move || {
+                        let settings = settings.get();
+                        settings
+                            .custom_themes
+                            .iter()
+                            .map(|theme| {
+                                let selected = settings.custom_theme.as_ref() == Some(&theme.name);
+                                view! {
+                                    <option
+                                        value=format!("{}{}", CUSTOM_PREFIX, theme.name)
+                                        selected=selected>
+                                        {theme.name.clone()}
+                                    </option>
+                                }
+                            })
+                            .collect_view()
+                    }}
+                </select>
+                <button type="button" on:click=move |_| new_theme()>"Customise..."</button>
+            </label>
+            <label>
+                "Colour-blind safe colours"
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().colour_blind
+                    on:change=move |ev| {
+                        let enabled = event_target_checked(&ev);
+                        update(&|s| s.colour_blind = enabled);
+                    }
+                />
+            </label>
+            <Show when=move || settings.get().custom_theme.is_some()>
+                <div class="settings-row theme-editor">
+                    <label>
+                        "Name"
+                        <input
+                            maxlength="32"
+                            prop:value=move || settings.get().custom_theme.unwrap_or_default()
+                            on:change=move |ev| rename_theme(event_target_value(&ev))
+                        />
+                    </label>
+                    <label>
+                        "Based on"
+                        <select on:change=move |ev| {
+                            if let Some(base) = Theme::from_id(&event_target_value(&ev)) {
+                                update(&|s| {
+                                    if let Some(theme) = s.active_custom_theme_mut() {
+                                        theme.base = base;
+                                    }
+                                });
+                            }
+                        }>
+                            {Theme::ALL
+                                .into_iter()
+                                .map(|theme| {
+                                    view! {
+                                        <option
+                                            value=theme.id()
+                                            selected=move || {
+                                                settings
+                                                    .get()
+                                                    .active_custom_theme()
+                                                    .is_some_and(|custom| custom.base == theme)
+                                            }>
+                                            {theme.label()}
+                                        </option>
+                                    }
+                                })
+                                .collect_view()}
+                        </select>
+                    </label>
+                    <button type="button" on:click=move |_| delete_theme()>"Delete theme"</button>
+                    <div class="theme-colors">
+                        {THEME_COLORS
+                            .into_iter()
+                            .map(|(name, label)| {
+                                view! {
+                                    <label>
+                                        {label}
+                                        <input
+                                            type="color"
+                                            prop:value=move || {
+                                                settings.track();
+                                                current_color(name)
+                                            }
+                                            on:change=move |ev| {
+                                                let color = event_target_value(&ev);
+                                                update(&|s| {
+                                                    if let Some(theme) = s.active_custom_theme_mut() {
+                                                        theme.colors.insert(name.to_string(), color.clone());
+                                                    }
+                                                });
+                                            }
+                                        />
+                                    </label>
+                                }
+                            })
+                            .collect_view()}
+                    </div>
+                </div>
+            </Show>
+            <label>
+                "Font size"
+                <input
+                    type="range"
+                    min=MIN_FONT_SIZE
+                    max=MAX_FONT_SIZE
+                    step="4"
+                    prop:value=move || settings.get().font_size
+                    on:change=move |ev| {
+                        if let Ok(font_size) = event_target_value(&ev).parse::<u32>() {
+                            update(&|s| s.font_size = font_size);
+                        }
+                    }
+                />
+            </label>
+            <label>
+                "Look-ahead (characters)"
+                <input
+                    type="number"
+                    min="0"
+                    max=MAX_LOOK_AHEAD
+                    prop:value=move || settings.get().look_ahead
+                    on:change=move |ev| {
+                        if let Ok(count) = event_target_value(&ev).parse::<usize>() {
+                            update(&|s| s.look_ahead = count.min(MAX_LOOK_AHEAD));
+                        }
+                    }
+                />
+            </label>
+            <label>
+                "Race a ghost"
+                <select on:change=move |ev| {
+                    if let Some(ghost) = GhostKind::from_id(&event_target_value(&ev)) {
+                        update(&|s| s.ghost = ghost);
+                    }
+                }>
+                    {GhostKind::ALL
+                        .into_iter()
+                        .map(|ghost| {
+                            view! {
+                                <option
+                                    value=ghost.id()
+                                    selected=move || settings.get().ghost == ghost>
+                                    {ghost.label()}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+            </label>
+            <label>
+                "Finger guide"
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().finger_guide
+                    on:change=move |ev| {
+                        let enabled = event_target_checked(&ev);
+                        update(&|s| s.finger_guide = enabled);
+                    }
+                />
+            </label>
+            <label>
+                "Numeric keypad"
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().numpad_visible
+                    on:change=move |ev| {
+                        let visible = event_target_checked(&ev);
+                        update(&|s| s.numpad_visible = visible);
+                    }
+                />
+            </label>
+            <label>
+                "Sound"
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().sound_enabled
+                    on:change=move |ev| {
+                        let enabled = event_target_checked(&ev);
+                        update(&|s| s.sound_enabled = enabled);
+                    }
+                />
+            </label>
+            <label>
+                "Volume"
+                <input
+                    type="range"
+                    min="0"
+                    max="100"
+                    step="5"
+                    disabled=move || !settings.get().sound_enabled
+                    prop:value=move || settings.get().sound_volume
+                    on:change=move |ev| {
+                        if let Ok(volume) = event_target_value(&ev).parse::<u32>() {
+                            update(&|s| s.sound_volume = volume.min(100));
+                        }
+                    }
+                />
+            </label>
+            <div class="settings-row">
+                "Play"
+                <label>
+                    <input
+                        type="checkbox"
+                        disabled=move || !settings.get().sound_enabled
+                        prop:checked=move || settings.get().hit_sound
+                        on:change=move |ev| {
+                            let enabled = event_target_checked(&ev);
+                            update(&|s| s.hit_sound = enabled);
+                        }
+                    />
+                    "Hit click"
+                </label>
+                <label>
+                    <input
+                        type="checkbox"
+                        disabled=move || !settings.get().sound_enabled
+                        prop:checked=move || settings.get().miss_sound
+                        on:change=move |ev| {
+                            let enabled = event_target_checked(&ev);
+                            update(&|s| s.miss_sound = enabled);
+                        }
+                    />
+                    "Miss tone"
+                </label>
+                <label>
+                    <input
+                        type="checkbox"
+                        disabled=move || !settings.get().sound_enabled
+                        prop:checked=move || settings.get().chime_sound
+                        on:change=move |ev| {
+                            let enabled = event_target_checked(&ev);
+                            update(&|s| s.chime_sound = enabled);
+                        }
+                    />
+                    "Record chime"
+                </label>
+            </div>
+            <label>
+                "Idle timeout (s, 0 = off)"
+                <input
+                    type="number"
+                    min="0"
+                    max="600"
+                    prop:value=move || settings.get().idle_timeout_secs
+                    on:change=move |ev| {
+                        if let Ok(secs) = event_target_value(&ev).parse::<u32>() {
+                            update(&|s| s.idle_timeout_secs = secs);
+                        }
+                    }
+                />
+            </label>
+            <label>
+                "Test length (0 = endless)"
+                <input
+                    type="number"
+                    min="0"
+                    max="1000"
+                    prop:value=move || settings.get().test_length
+                    on:change=move |ev| {
+                        if let Ok(length) = event_target_value(&ev).parse::<usize>() {
+                            update(&|s| s.test_length = length);
+                        }
+                    }
+                />
+            </label>
+            <label>
+                "Daily goal (0 = none)"
+                <input
+                    type="number"
+                    min="0"
+                    max="100000"
+                    prop:value=move || settings.get().daily_goal
+                    on:change=move |ev| {
+                        if let Ok(goal) = event_target_value(&ev).parse::<u32>() {
+                            update(&|s| s.daily_goal = goal);
+                        }
+                    }
+                />
+                <select on:change=move |ev| {
+                    if let Some(goal_kind) = GoalKind::from_id(&event_target_value(&ev)) {
+                        update(&|s| s.goal_kind = goal_kind);
+                    }
+                }>
+                    {GoalKind::ALL
+                        .into_iter()
+                        .map(|goal_kind| {
+                            view! {
+                                <option
+                                    value=goal_kind.id()
+                                    selected=move || settings.get().goal_kind == goal_kind>
+                                    {goal_kind.label()}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+            </label>
+            <label>
+                "Remind me at"
+                <select
+                    disabled=move || settings.get().daily_goal == 0
+                    on:change=move |ev| {
+                        let hour = event_target_value(&ev).parse::<u32>().ok();
+                        update(&|s| s.reminder_hour = hour);
+                    }>
+                    <option value="" selected=move || settings.get().reminder_hour.is_none()>
+                        "Never"
+                    </option>
+                    {(0..24)
+                        .map(|hour| {
+                            view! {
+                                <option
+                                    value=hour
+                                    selected=move || settings.get().reminder_hour == Some(hour)>
+                                    {format!("{:02}:00", hour)}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+            </label>
+        </div>
+    }
+}
@@ -0,0 +1,364 @@
+use crate::practice_text::wrap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    Rust,
+    Python,
+    JavaScript,
+    Shell,
+}
+
+impl Language {
+    pub const ALL: [Language; 4] = [
+        Language::Rust,
+        Language::Python,
+        Language::JavaScript,
+        Language::Shell,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::Python => "python",
+            Language::JavaScript => "javascript",
+            Language::Shell => "shell",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Language::Rust => "Rust",
+            Language::Python => "Python",
+            Language::JavaScript => "JavaScript",
+            Language::Shell => "Shell",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Language::ALL.into_iter().find(|item| item.id() == id)
+    }
+
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "rs" => Some(Language::Rust),
+            "py" => Some(Language::Python),
+            "js" | "mjs" | "ts" => Some(Language::JavaScript),
+            "sh" | "bash" | "zsh" => Some(Language::Shell),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snippet {
+    pub language: Language,
+    pub title: String,
+    pub code: String,
+}
+
+const BUNDLED: &[(Language, &str, &str)] = &[
+    (
+        Language::Rust,
+        "match on an Option",
+        r##"fn describe(value: Option<u32>) -> String {
+    match value {
+        Some(n) if n > 10 => format!("big: {}", n),
+        Some(n) => format!("small: {}", n),
+        None => String::from("nothing"),
+    }
+}"##,
+    ),
+    (
+        Language::Rust,
+        "iterator chain",
+        r##"let total: u64 = items
+    .iter()
+    .filter(|item| item.enabled)
+    .map(|item| item.cost * 2)
+    .sum();"##,
+    ),
+    (
+        Language::Rust,
+        "struct with impl",
+        r##"#[derive(Debug, Clone)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Self {
+        Point { x, y }
+    }
+}"##,
+    ),
+    (
+        Language::Python,
+        "dictionary comprehension",
+        r##"def index_by_id(rows):
+    return {row["id"]: row for row in rows if row.get("id")}"##,
+    ),
+    (
+        Language::Python,
+        "context manager",
+        r##"with open(path, "r", encoding="utf-8") as f:
+    for line_no, line in enumerate(f, start=1):
+        if line.strip().startswith("#"):
+            continue
+        print(f"{line_no}: {line.rstrip()}")"##,
+    ),
+    (
+        Language::Python,
+        "dataclass",
+        r##"@dataclass
+class Config:
+    name: str
+    retries: int = 3
+    tags: list[str] = field(default_factory=list)"##,
+    ),
+    (
+        Language::JavaScript,
+        "arrow functions",
+        r##"const evens = numbers
+  .filter((n) => n % 2 === 0)
+  .map((n) => ({ value: n, square: n * n }));"##,
+    ),
+    (
+        Language::JavaScript,
+        "async fetch",
+        r##"async function load(url) {
+  const res = await fetch(url);
+  if (!res.ok) {
+    throw new Error(`HTTP ${res.status}`);
+  }
+  return res.json();
+}"##,
+    ),
+    (
+        Language::JavaScript,
+        "destructuring",
+        r##"const { name, age = 0, ...rest } = user;
+const [first, , third] = list;"##,
+    ),
+    (
+        Language::Shell,
+        "loop over files",
+        r##"for f in *.log; do
+  [ -s "$f" ] && gzip -9 "$f"
+done"##,
+    ),
+    (
+        Language::Shell,
+        "pipeline",
+        r##"grep -E '^(ERROR|WARN)' app.log | sort | uniq -c | sort -rn > summary.txt"##,
+    ),
+    (
+        Language::Shell,
+        "function with default",
+        r##"backup() {
+  local dest="${1:-$HOME/backup}"
+  mkdir -p "$dest" && cp -r ./src "$dest/$(date +%F)"
+}"##,
+    ),
+];
+
+pub fn bundled_snippets() -> Vec<Snippet> {
+    BUNDLED
+        .iter()
+        .map(|(language, title, code)| Snippet {
+            language: *language,
+            title: title.to_string(),
+            code: code.to_string(),
+        })
+        .collect()
+}
+
+/// Picks one of the `language` snippets; `pick` chooses an index below the
+/// count it's given.
+pub fn pick_snippet(
+    snippets: &[Snippet],
+    language: Language,
+    pick: impl FnOnce(usize) -> usize,
+) -> Option<Snippet> {
+    let matching: Vec<&Snippet> = snippets.iter().filter(|s| s.language == language).collect();
+    if matching.is_empty() {
+        return None;
+    }
+    Some(matching[pick(matching.len())].clone())
+}
+
+/// What the prompt shows while drilling a line.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LineView {
+    pub typed: String,
+    /// Positions in `typed` that were got wrong and passed over
+    pub errors: Vec<usize>,
+    pub current: Option<char>,
+    pub remaining: String,
+    pub next_line: Option<String>,
+}
+
+/// Walks a snippet one character at a time, line by line.
+///
+/// Indentation is typed as written, tabs included, unless `skip_indent` is
+/// set (editors insert it for you). Every line but the last ends with a
+/// `line_break` target: Enter for code, or a space for prose that was only
+/// wrapped for display.
+#[derive(Debug, Clone)]
+pub struct SnippetDrill {
+    pub title: String,
+    lines: Vec<Vec<char>>,
+    line_break: char,
+    skip_indent: bool,
+    line: usize,
+    col: usize,
+    /// (line, col) of targets got wrong and passed over
+    errors: Vec<(usize, usize)>,
+}
+
+impl SnippetDrill {
+    pub fn new(snippet: &Snippet, skip_indent: bool) -> Self {
+        Self::from_text(&snippet.title, &snippet.code, '\n', skip_indent)
+    }
+
+    pub fn from_passage(title: &str, passage: &str) -> Self {
+        Self::from_text(title, &wrap(passage), ' ', false)
+    }
+
+    fn from_text(title: &str, text: &str, line_break: char, skip_indent: bool) -> Self {
+        let lines: Vec<Vec<char>> = text
+            .replace("\r\n", "\n")
+            .lines()
+            .map(|line| line.trim_end().chars().collect::<Vec<char>>())
+            .filter(|line| !line.is_empty())
+            .collect();
+
+        let mut drill = SnippetDrill {
+            title: title.to_string(),
+            lines,
+            line_break,
+            skip_indent,
+            line: 0,
+            col: 0,
+            errors: Vec::new(),
+        };
+        drill.start_line();
+        drill
+    }
+
+    fn start_line(&mut self) {
+        self.col = match self.lines.get(self.line) {
+            Some(line) if self.skip_indent => line.iter().take_while(|c| c.is_whitespace()).count(),
+            _ => 0,
+        };
+    }
+
+    fn is_last_line(&self) -> bool {
+        self.line + 1 >= self.lines.len()
+    }
+
+    pub fn target(&self) -> Option<char> {
+        let line = self.lines.get(self.line)?;
+        match line.get(self.col) {
+            Some(c) => Some(*c),
+            None if !self.is_last_line() => Some(self.line_break),
+            None => None,
+        }
+    }
+
+    /// Moves past the current target. Returns `false` once the snippet is done.
+    pub fn advance(&mut self) -> bool {
+        let Some(line) = self.lines.get(self.line) else {
+            return false;
+        };
+        if self.col < line.len() {
+            self.col += 1;
+        } else {
+            self.line += 1;
+            self.start_line();
+        }
+        self.target().is_some()
+    }
+
+    /// Marks the current target as got wrong, before moving past it.
+    pub fn mark_error(&mut self) {
+        self.errors.push((self.line, self.col));
+    }
+
+    /// The next `count` targets after the current one.
+    pub fn upcoming(&self, count: usize) -> Vec<char> {
+        let mut drill = self.clone();
+        std::iter::from_fn(|| drill.advance().then(|| drill.target()).flatten())
+            .take(count)
+            .collect()
+    }
+
+    pub fn line_view(&self) -> LineView {
+        let Some(line) = self.lines.get(self.line) else {
+            return LineView::default();
+        };
+        let col = self.col.min(line.len());
+        LineView {
+            typed: line[..col].iter().collect(),
+            errors: self
+                .errors
+                .iter()
+                .filter(|&&(line, _)| line == self.line)
+                .map(|&(_, col)| col)
+                .collect(),
+            current: self.target(),
+            remaining: line.get(col + 1..).map(|rest| rest.iter().collect()).unwrap_or_default(),
+            next_line: self.lines.get(self.line + 1).map(|next| next.iter().collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(code: &str) -> Snippet {
+        Snippet {
+            language: Language::Rust,
+            title: "test".to_string(),
+            code: code.to_string(),
+        }
+    }
+
+    fn targets(mut drill: SnippetDrill) -> String {
+        let mut typed = String::new();
+        while let Some(c) = drill.target() {
+            typed.push(c);
+            drill.advance();
+        }
+        typed
+    }
+
+    #[test]
+    fn indentation_is_typed_by_default() {
+        let drill = SnippetDrill::new(&snippet("if x {\n\ty();\n    z();\n}"), false);
+        assert_eq!(targets(drill), "if x {\n\ty();\n    z();\n}");
+    }
+
+    #[test]
+    fn indentation_can_be_skipped() {
+        let drill = SnippetDrill::new(&snippet("\tif x {\n\t\ty();\n\t}"), true);
+        assert_eq!(targets(drill), "if x {\ny();\n}");
+    }
+
+    #[test]
+    fn blank_lines_and_trailing_space_are_dropped() {
+        let drill = SnippetDrill::new(&snippet("a  \r\n\n   \nb"), false);
+        assert_eq!(targets(drill), "a\nb");
+    }
+
+    #[test]
+    fn passages_break_lines_with_spaces() {
+        let passage = ["word"; 20].join(" ");
+        assert_eq!(
+            targets(SnippetDrill::from_passage("test", &passage)),
+            passage
+        );
+    }
+}
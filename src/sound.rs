@@ -0,0 +1,214 @@
+use crate::settings::Settings;
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+
+// Just the parts of the Web Audio API the sounds below need.
+#[wasm_bindgen]
+extern "C" {
+    type AudioContext;
+    #[wasm_bindgen(constructor, catch)]
+    fn new() -> Result<AudioContext, JsValue>;
+    #[wasm_bindgen(method, getter, js_name = currentTime)]
+    fn current_time(this: &AudioContext) -> f64;
+    #[wasm_bindgen(method, getter)]
+    fn destination(this: &AudioContext) -> AudioNode;
+    #[wasm_bindgen(method, js_name = createOscillator)]
+    fn create_oscillator(this: &AudioContext) -> OscillatorNode;
+    #[wasm_bindgen(method, js_name = createGain)]
+    fn create_gain(this: &AudioContext) -> GainNode;
+
+    type AudioNode;
+    #[wasm_bindgen(method)]
+    fn connect(this: &AudioNode, destination: &AudioNode) -> AudioNode;
+
+    #[wasm_bindgen(extends = AudioNode)]
+    type OscillatorNode;
+    #[wasm_bindgen(method, setter = type)]
+    fn set_type(this: &OscillatorNode, wave: &str);
+    #[wasm_bindgen(method, getter)]
+    fn frequency(this: &OscillatorNode) -> AudioParam;
+    #[wasm_bindgen(method)]
+    fn start(this: &OscillatorNode, when: f64);
+    #[wasm_bindgen(method)]
+    fn stop(this: &OscillatorNode, when: f64);
+
+    #[wasm_bindgen(extends = AudioNode)]
+    type GainNode;
+    #[wasm_bindgen(method, getter)]
+    fn gain(this: &GainNode) -> AudioParam;
+
+    type AudioParam;
+    #[wasm_bindgen(method, js_name = setValueAtTime)]
+    fn set_value_at_time(this: &AudioParam, value: f32, time: f64) -> AudioParam;
+    #[wasm_bindgen(method, js_name = exponentialRampToValueAtTime)]
+    fn exponential_ramp_to_value_at_time(this: &AudioParam, value: f32, time: f64) -> AudioParam;
+}
+
+thread_local! {
+    // Browsers only let audio start after user input, so this is made on the
+    // first sound rather than at startup.
+    static CONTEXT: RefCell<Option<AudioContext>> = const { RefCell::new(None) };
+}
+
+// Exponential ramps can't reach zero, so notes fade out to this instead.
+const SILENT: f32 = 0.0001;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sound {
+    /// A short click for a hit
+    Hit,
+    /// A low buzz for a miss
+    Miss,
+    /// A rising chime for a new record or a passed lesson
+    Chime,
+}
+
+impl Sound {
+    fn enabled(&self, settings: &Settings) -> bool {
+        settings.sound_enabled
+            && settings.sound_volume > 0
+            && match self {
+                Sound::Hit => settings.hit_sound,
+                Sound::Miss => settings.miss_sound,
+                Sound::Chime => settings.chime_sound,
+            }
+    }
+
+    // (wave, frequency in Hz, start and length in seconds) of each note.
+    fn notes(&self) -> &'static [(&'static str, f32, f64, f64)] {
+        match self {
+            Sound::Hit => &[("square", 1800.0, 0.0, 0.02)],
+            Sound::Miss => &[("sawtooth", 140.0, 0.0, 0.18)],
+            Sound::Chime => &[
+                ("sine", 660.0, 0.0, 0.4),
+                ("sine", 880.0, 0.08, 0.4),
+                ("sine", 1320.0, 0.16, 0.6),
+            ],
+        }
+    }
+
+    // Loudness at full volume; the click and buzz sit under the chime.
+    fn level(&self) -> f32 {
+        match self {
+            Sound::Hit => 0.15,
+            Sound::Miss => 0.2,
+            Sound::Chime => 0.3,
+        }
+    }
+
+    // How loud the notes start at the settings' volume.
+    fn peak(&self, settings: &Settings) -> f32 {
+        let volume = settings.sound_volume.min(100) as f32 / 100.0;
+        (self.level() * volume).max(SILENT)
+    }
+}
+
+fn play_notes(context: &AudioContext, sound: Sound, peak: f32) {
+    let now = context.current_time();
+    for &(wave, frequency, start, length) in sound.notes() {
+        let oscillator = context.create_oscillator();
+        let envelope = context.create_gain();
+        oscillator.set_type(wave);
+        oscillator.frequency().set_value_at_time(frequency, now);
+        envelope.gain().set_value_at_time(peak, now + start);
+        envelope
+            .gain()
+            .exponential_ramp_to_value_at_time(SILENT, now + start + length);
+        oscillator.connect(&envelope);
+        envelope.connect(&context.destination());
+        oscillator.start(now + start);
+        oscillator.stop(now + start + length);
+    }
+}
+
+/// Plays `sound` if the settings allow it. Does nothing where Web Audio
+/// isn't available.
+pub fn play(settings: &Settings, sound: Sound) {
+    if !sound.enabled(settings) {
+        return;
+    }
+    let peak = sound.peak(settings);
+    CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+        if context.is_none() {
+            *context = AudioContext::new().ok();
+        }
+        if let Some(context) = context.as_ref() {
+            play_notes(context, sound, peak);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOUNDS: [Sound; 3] = [Sound::Hit, Sound::Miss, Sound::Chime];
+
+    fn sound_on() -> Settings {
+        Settings {
+            sound_enabled: true,
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn sound_is_off_by_default() {
+        let settings = Settings::default();
+        assert!(SOUNDS.iter().all(|sound| !sound.enabled(&settings)));
+        assert!(SOUNDS.iter().all(|sound| sound.enabled(&sound_on())));
+    }
+
+    #[test]
+    fn each_sound_has_its_own_toggle() {
+        let settings = Settings {
+            miss_sound: false,
+            ..sound_on()
+        };
+        assert!(Sound::Hit.enabled(&settings));
+        assert!(!Sound::Miss.enabled(&settings));
+        assert!(Sound::Chime.enabled(&settings));
+    }
+
+    #[test]
+    fn no_volume_is_no_sound() {
+        let settings = Settings {
+            sound_volume: 0,
+            ..sound_on()
+        };
+        assert!(SOUNDS.iter().all(|sound| !sound.enabled(&settings)));
+    }
+
+    #[test]
+    fn volume_scales_each_sound_up_to_its_level() {
+        let full = Settings {
+            sound_volume: 100,
+            ..sound_on()
+        };
+        let half = Settings {
+            sound_volume: 50,
+            ..sound_on()
+        };
+        let too_loud = Settings {
+            sound_volume: 250,
+            ..sound_on()
+        };
+        for sound in SOUNDS {
+            assert_eq!(sound.peak(&full), sound.level());
+            assert_eq!(sound.peak(&half), sound.level() / 2.0);
+            assert_eq!(sound.peak(&too_loud), sound.level());
+        }
+        // The chime stands out over the click and buzz.
+        assert!(Sound::Chime.level() > Sound::Hit.level().max(Sound::Miss.level()));
+    }
+
+    #[test]
+    fn the_chime_rises() {
+        let notes = Sound::Chime.notes();
+        assert!(notes.windows(2).all(|pair| {
+            let (_, low, low_start, _) = pair[0];
+            let (_, high, high_start, _) = pair[1];
+            high > low && high_start > low_start
+        }));
+    }
+}
@@ -1,9 +1,9 @@
-use crate::ascii_chars::AsciiChars;
+use crate::target_char::TargetChar;
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, PartialOrd, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Serialize, Deserialize)]
 pub enum HitType {
     /// Hit with a timestamp and the reaction time in milliseconds
     Hit(NaiveDateTime, u64),
@@ -13,13 +13,23 @@ pub enum HitType {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct StatRecord {
-    pub char_id: AsciiChars,
+    pub char_id: TargetChar,
     pub results: Vec<HitType>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Stats {
-    records: HashMap<AsciiChars, StatRecord>,
+    records: HashMap<TargetChar, StatRecord>,
+    #[serde(default)]
+    current_streak: usize,
+    #[serde(default)]
+    longest_streak: usize,
+    /// Shifted characters typed with the Shift key known
+    #[serde(default)]
+    shift_presses: usize,
+    /// How many of those used the opposite-hand Shift
+    #[serde(default)]
+    opposite_shift_presses: usize,
 }
 
 #[derive(Debug)]
@@ -31,34 +41,67 @@ pub struct StatSummary {
     pub hit_avg: u64,
 }
 
+#[derive(Debug)]
+pub struct RunSummary {
+    pub total_hits: usize,
+    pub total_misses: usize,
+    pub hit_avg: u64,
+    pub accuracy: f64,
+    pub longest_streak: usize,
+}
+
 impl Stats {
     pub fn new() -> Self {
         Stats {
             records: HashMap::new(),
+            current_streak: 0,
+            longest_streak: 0,
+            shift_presses: 0,
+            opposite_shift_presses: 0,
         }
     }
 
-    pub fn add_hit(&mut self, char_id: AsciiChars, timestamp: NaiveDateTime, reaction_time: u64) {
-        let record = self.records.entry(char_id.clone()).or_insert(StatRecord {
+    pub fn add_hit(&mut self, char_id: TargetChar, timestamp: NaiveDateTime, reaction_time: u64) {
+        let record = self.records.entry(char_id).or_insert(StatRecord {
             char_id,
             results: Vec::new(),
         });
         record.results.push(HitType::Hit(timestamp, reaction_time));
+
+        self.current_streak += 1;
+        self.longest_streak = self.longest_streak.max(self.current_streak);
     }
 
-    pub fn add_miss(&mut self, char_id: AsciiChars, timestamp: NaiveDateTime) {
-        let record = self.records.entry(char_id.clone()).or_insert(StatRecord {
+    pub fn add_miss(&mut self, char_id: TargetChar, timestamp: NaiveDateTime) {
+        let record = self.records.entry(char_id).or_insert(StatRecord {
             char_id,
             results: Vec::new(),
         });
         record.results.push(HitType::Miss(timestamp));
+
+        self.current_streak = 0;
+    }
+
+    pub fn add_shift_use(&mut self, opposite_hand: bool) {
+        self.shift_presses += 1;
+        if opposite_hand {
+            self.opposite_shift_presses += 1;
+        }
+    }
+
+    /// Percentage of shifted characters typed with the opposite-hand Shift.
+    pub fn get_opposite_shift_rate(&self) -> Option<f64> {
+        if self.shift_presses == 0 {
+            return None;
+        }
+        Some(self.opposite_shift_presses as f64 * 100.0 / self.shift_presses as f64)
     }
 
-    pub fn get_records(&self) -> &HashMap<AsciiChars, StatRecord> {
+    pub fn get_records(&self) -> &HashMap<TargetChar, StatRecord> {
         &self.records
     }
 
-    pub fn get_record(&self, char_id: &AsciiChars) -> Option<&StatRecord> {
+    pub fn get_record(&self, char_id: &TargetChar) -> Option<&StatRecord> {
         self.records.get(char_id)
     }
 
@@ -86,11 +129,58 @@ impl Stats {
             .sum()
     }
 
+    pub fn get_longest_streak(&self) -> usize {
+        self.longest_streak
+    }
+
     pub fn reset(&mut self) {
         self.records.clear();
+        self.current_streak = 0;
+        self.longest_streak = 0;
+        self.shift_presses = 0;
+        self.opposite_shift_presses = 0;
     }
 
-    pub fn get_char_stats(&self, char_id: &AsciiChars) -> Option<StatSummary> {
+    pub fn get_run_summary(&self) -> RunSummary {
+        // Summarises everything typed since the last reset, which is what
+        // personal bests are measured against.
+        let total_hits = self.get_total_hit_count();
+        let total_misses = self.get_total_miss_count();
+        let total_ms: u64 = self
+            .records
+            .values()
+            .flat_map(|r| r.results.iter())
+            .filter_map(|hit| {
+                if let HitType::Hit(_, reaction_time) = hit {
+                    Some(*reaction_time)
+                } else {
+                    None
+                }
+            })
+            .sum();
+
+        let hit_avg = if total_hits > 0 {
+            total_ms / total_hits as u64
+        } else {
+            0
+        };
+        let attempts = total_hits + total_misses;
+        let accuracy = if attempts > 0 {
+            total_hits as f64 * 100.0 / attempts as f64
+        } else {
+            0.0
+        };
+
+        RunSummary {
+            total_hits,
+            total_misses,
+            hit_avg,
+            accuracy,
+            longest_streak: self.longest_streak,
+        }
+    }
+
+    pub fn get_char_stats(&self, char_id: &TargetChar) -> Option<StatSummary> {
         self.records.get(char_id).map(|record| {
             let hits: Vec<u64> = record
                 .results
@@ -128,9 +218,9 @@ impl Stats {
     pub fn generate_html_report(&self) -> String {
         let mut html = String::with_capacity(4096);
         html.push_str("<html><body><h1>Status Report</h1><table border='1'>");
-        html.push_str("<tr><th>Character</th><th>Hits</th><th>LowMS</th><th>HighMS</th><th>AvgMS</th><th>Misses</th></tr>");
+        html.push_str("<tr><th>Character</th><th>Name</th><th>Hits</th><th>LowMS</th><th>HighMS</th><th>AvgMS</th><th>Misses</th></tr>");
 
-        let mut keys: Vec<&AsciiChars> = self.records.keys().collect();
+        let mut keys: Vec<&TargetChar> = self.records.keys().collect();
         keys.sort_unstable();
 
         for (char_id) in keys {
@@ -143,8 +233,9 @@ impl Stats {
             });
 
             html.push_str(&format!(
-                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
-                char_id.as_char(),
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                char_id.label(),
+                char_id.name().unwrap_or_default(),
                 stats.total_hits,
                 stats.hit_low,
                 stats.hit_high,
@@ -153,7 +244,14 @@ impl Stats {
             ));
         }
 
-        html.push_str("</table></body></html>");
+        html.push_str("</table>");
+        if let Some(rate) = self.get_opposite_shift_rate() {
+            html.push_str(&format!(
+                "<p>Opposite-hand Shift: {} of {} ({:.0}%)</p>",
+                self.opposite_shift_presses, self.shift_presses, rate
+            ));
+        }
+        html.push_str("</body></html>");
         html
     }
 }
@@ -1,4 +1,10 @@
-use crate::ascii_chars::AsciiChars;
+use crate::layout::{Finger, Layout, KEY_CODES, PHYSICAL_KEYS};
+use crate::target_char::TargetChar;
+use leptos::web_sys::Element;
+
+// Set on the element holding a copy of the keyboards made by `with_prefix`,
+// so `find_key` knows what its ids start with.
+const ID_PREFIX_ATTR: &str = "data-id-prefix";
 
 pub struct SvgKeyboard {}
 
@@ -8,73 +14,170 @@ impl SvgKeyboard {
     }
 
     pub const fn render() -> &'static str {
-        // This generates an image of a standard QWERTY keyboard layout
-        // with the specified key(s) highlighted - either key, or
-        // SHIFT + key.
+        // This generates an image of a standard keyboard; the key caps are
+        // relabelled for the selected layout and the key(s) for the target
+        // highlighted.
         //let shift_needed = shift_key_needed(&key_char);
 
         &SVG_KEYBOARD
     }
 
-    pub fn get_css_ids(key_char: &AsciiChars) -> (String, Option<&str>) {
-        // This returns the CSS IDs for the key(s) to highlight.
-        // If shift is needed, it returns both the key and the shifted key.
-        let shift_needed = shift_key_needed(key_char);
-        let css_id = map_ascii_to_key_id(key_char);
-        if shift_needed {
-            (css_id, Some("key-shift-left"))
-        } else {
-            (css_id, None)
+    pub const fn render_numpad() -> &'static str {
+        // The numeric keypad is a separate image so it can be shown only
+        // when wanted.
+        &SVG_NUMPAD
+    }
+
+    /// `svg` with every id, and every reference to one, prefixed, for a
+    /// second copy on the page: ids have to stay unique across the page.
+    pub fn with_prefix(svg: &str, prefix: &str) -> String {
+        svg.replace(" id=\"", &format!(" id=\"{}", prefix))
+            .replace("url(#", &format!("url(#{}", prefix))
+    }
+
+    pub fn get_css_ids(target: &TargetChar, layout: Layout) -> Vec<&'static str> {
+        // This returns the CSS IDs for the key(s) to highlight: every key in
+        // the sequence that types the character, plus Shift (on the other
+        // hand) and AltGr when they need to be held.
+        if target.is_numpad() {
+            return map_numpad_to_key_id(target.as_char()).into_iter().collect();
+        }
+        let mut ids = Vec::new();
+        for stroke in layout.key_sequence(target.as_char()).unwrap_or_default() {
+            let modifiers = [
+                layout.stroke_shift_key(&stroke),
+                Some("key-alt-right").filter(|_| stroke.alt_gr),
+            ];
+            for id in std::iter::once(stroke.key).chain(modifiers.into_iter().flatten()) {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
         }
+        ids
     }
-}
 
-fn shift_key_needed(key_char: &AsciiChars) -> bool {
-    let c = key_char.as_char();
-    match c {
-        'A'..='Z' => true,
-        '<' | '>' | '?' | '~' | '!' | '@' | '#' | '$' | '%' | '^' | '&' | '*' | '(' | ')' | '_'
-        | '+' | '{' | '}' | '|' | ':' | '"' => true,
-        _ => false,
+    pub fn mark_fingers(keyboard: &Element, layout: Layout) {
+        // Tags every key with the finger that presses it; the finger guide
+        // colours keys by these classes.
+        let keys = keyboard.get_elements_by_tag_name("g");
+        for i in 0..keys.length() {
+            let Some(key) = keys.item(i) else {
+                continue;
+            };
+            let Some(finger) = layout.finger(&key.id()) else {
+                continue;
+            };
+            let classes = key.class_list();
+            for other in Finger::ALL {
+                let _ = classes.remove_1(&format!("finger-{}", other.id()));
+            }
+            let _ = classes.add_1(&format!("finger-{}", finger.id()));
+        }
+    }
+
+    pub fn relabel(keyboard: &Element, layout: Layout) {
+        // The SVG is drawn with US key caps; rewrite the character keys to
+        // show what they type on `layout`.
+        if let Some(alt) = Self::find_key(keyboard, "key-alt-right") {
+            let label = if layout == Layout::Qwerty { "Alt" } else { "AltGr" };
+            if let Some(text) = alt.get_elements_by_tag_name("text").item(0) {
+                text.set_text_content(Some(label));
+            }
+        }
+        for key in PHYSICAL_KEYS {
+            let (Some(el), Some((normal, shifted))) =
+                (Self::find_key(keyboard, key), layout.key_caps(key))
+            else {
+                continue;
+            };
+            let labels = el.get_elements_by_tag_name("text");
+            let cap = |c: char| if c == ' ' { String::new() } else { c.to_string() };
+            if labels.length() >= 2 {
+                if let (Some(top), Some(bottom)) = (labels.item(0), labels.item(1)) {
+                    top.set_text_content(Some(&cap(shifted)));
+                    bottom.set_text_content(Some(&cap(normal)));
+                }
+            } else if let Some(label) = labels.item(0) {
+                // Letter keys only have room for one label.
+                let text = if normal.to_uppercase().eq(std::iter::once(shifted)) {
+                    cap(shifted)
+                } else {
+                    format!("{}{}", cap(shifted), cap(normal))
+                };
+                label.set_text_content(Some(&text));
+            }
+        }
     }
-}
 
-fn map_ascii_to_key_id(key_char: &AsciiChars) -> String {
-    // This maps the ASCII character to the corresponding key ID in the SVG.
-    // For example, 'A' maps to 'key-a', '1' maps to 'key-1', etc.
-    let c = key_char.as_char();
-    match c {
-        'A'..='Z' => format!("key-{}", c.to_ascii_lowercase()),
-        'a'..='z' => format!("key-{}", c),
-        '0'..='9' => format!("key-{}", c),
-        '-' => "key-minus".to_string(),
-        '=' => "key-equals".to_string(),
-        '[' | '{' => "key-l-bracket".to_string(),
-        ']' | '}' => "key-r-bracket".to_string(),
-        '\\' | '|' => "key-backslash".to_string(),
-        ';' | ':' => "key-semicolon".to_string(),
-        '\'' | '"' => "key-s-quote".to_string(),
-        ',' | '<' => "key-comma".to_string(),
-        '.' | '>' => "key-period".to_string(),
-        '/' | '?' => "key-slash".to_string(),
-        '`' | '~' => "key-tilde".to_string(),
-        '!' => "key-1".to_string(),
-        '@' => "key-2".to_string(),
-        '#' => "key-3".to_string(),
-        '$' => "key-4".to_string(),
-        '%' => "key-5".to_string(),
-        '^' => "key-6".to_string(),
-        '&' => "key-7".to_string(),
-        '*' => "key-8".to_string(),
-        '(' => "key-9".to_string(),
-        ')' => "key-0".to_string(),
-        '_' => "key-minus".to_string(),
-        '+' => "key-equals".to_string(),
+    /// The key with the given id within this copy of the keyboard.
+    pub fn find_key(keyboard: &Element, id: &str) -> Option<Element> {
+        let prefix = keyboard.get_attribute(ID_PREFIX_ATTR).unwrap_or_default();
+        keyboard
+            .query_selector(&format!("#{}{}", prefix, id))
+            .ok()
+            .flatten()
+    }
 
-        _ => String::new(), // Return an empty string for unsupported characters
+    pub fn key_for_code(code: &str) -> Option<&'static str> {
+        // Maps a `KeyboardEvent.code` (the physical key, whatever the layout)
+        // to its key on the keyboard images.
+        if let Some(i) = KEY_CODES.iter().position(|&c| c == code) {
+            return Some(PHYSICAL_KEYS[i]);
+        }
+        let id = match code {
+            "Tab" => "key-tab",
+            "CapsLock" => "key-caps",
+            "ShiftLeft" => "key-shift-left",
+            "ShiftRight" => "key-shift-right",
+            "Enter" => "key-enter",
+            "Backspace" => "key-back",
+            "Space" => "key-space",
+            "ControlLeft" => "key-ctrl-left",
+            "ControlRight" => "key-ctrl-right",
+            "AltLeft" => "key-alt-left",
+            "AltRight" => "key-alt-right",
+            "MetaLeft" => "key-win-left",
+            "ContextMenu" => "key-menu",
+            "NumLock" => "key-num-lock",
+            "NumpadEnter" => "key-num-enter",
+            "NumpadAdd" => return map_numpad_to_key_id('+'),
+            "NumpadSubtract" => return map_numpad_to_key_id('-'),
+            "NumpadMultiply" => return map_numpad_to_key_id('*'),
+            "NumpadDivide" => return map_numpad_to_key_id('/'),
+            "NumpadDecimal" => return map_numpad_to_key_id('.'),
+            _ => {
+                let digit = code.strip_prefix("Numpad")?.chars().next()?;
+                return map_numpad_to_key_id(digit);
+            }
+        };
+        Some(id)
     }
 }
 
+fn map_numpad_to_key_id(c: char) -> Option<&'static str> {
+    let id = match c {
+        '0' => "key-num-0",
+        '1' => "key-num-1",
+        '2' => "key-num-2",
+        '3' => "key-num-3",
+        '4' => "key-num-4",
+        '5' => "key-num-5",
+        '6' => "key-num-6",
+        '7' => "key-num-7",
+        '8' => "key-num-8",
+        '9' => "key-num-9",
+        '+' => "key-num-plus",
+        '-' => "key-num-minus",
+        '*' => "key-num-multiply",
+        '/' => "key-num-divide",
+        '.' => "key-num-decimal",
+        '\n' => "key-num-enter",
+        _ => return None,
+    };
+    Some(id)
+}
+
 const SVG_KEYBOARD: &'static str = r###"
 <svg viewBox="0 0 710 240" xmlns="http://www.w3.org/2000/svg" width="100%" height="auto" preserveAspectRatio="xMidYMid meet">
   <defs>
@@ -266,8 +369,13 @@ const SVG_KEYBOARD: &'static str = r###"
   <text x="652.0" y="125.0" font-size="14" text-anchor="middle">Enter</text>
 </g>
 <g id="key-shift-left">
-  <rect x="10" y="145" width="90" height="40" rx="6" fill="url(#keyGrad)" stroke="black" />
-  <text x="55.0" y="170.0" font-size="14" text-anchor="middle">Shift</text>
+  <rect x="10" y="145" width="43" height="40" rx="6" fill="url(#keyGrad)" stroke="black" />
+  <text x="31.5" y="170.0" font-size="12" text-anchor="middle">Shift</text>
+</g>
+<g id="key-intl">
+  <rect x="55" y="145" width="45" height="40" rx="6" fill="url(#keyGrad)" stroke="black" />
+  <text x="77.5" y="157" font-size="10" text-anchor="middle"></text>
+  <text x="77.5" y="177" font-size="14" text-anchor="middle"></text>
 </g>
 <g id="key-z">
   <rect x="102" y="145" width="45" height="40" rx="6" fill="url(#keyGrad)" stroke="black" />
@@ -350,3 +458,82 @@ const SVG_KEYBOARD: &'static str = r###"
 </g>
 </svg>
 "###;
+
+const SVG_NUMPAD: &'static str = r###"
+<svg viewBox="0 0 206 240" xmlns="http://www.w3.org/2000/svg" width="100%" height="auto" preserveAspectRatio="xMidYMid meet">
+  <defs>
+    <linearGradient id="numGrad" x1="0" y1="0" x2="0" y2="1">
+      <stop offset="0%" stop-color="#e0e0e0" />
+      <stop offset="100%" stop-color="#a0a0a0" />
+    </linearGradient>
+  </defs>
+<g id="key-num-lock">
+  <rect x="10" y="10" width="45" height="40" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="32.5" y="35.0" font-size="12" text-anchor="middle">Num</text>
+</g>
+<g id="key-num-divide">
+  <rect x="57" y="10" width="45" height="40" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="79.5" y="35.0" font-size="14" text-anchor="middle">/</text>
+</g>
+<g id="key-num-multiply">
+  <rect x="104" y="10" width="45" height="40" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="126.5" y="35.0" font-size="14" text-anchor="middle">*</text>
+</g>
+<g id="key-num-minus">
+  <rect x="151" y="10" width="45" height="40" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="173.5" y="35.0" font-size="14" text-anchor="middle">-</text>
+</g>
+<g id="key-num-7">
+  <rect x="10" y="55" width="45" height="40" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="32.5" y="80.0" font-size="14" text-anchor="middle">7</text>
+</g>
+<g id="key-num-8">
+  <rect x="57" y="55" width="45" height="40" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="79.5" y="80.0" font-size="14" text-anchor="middle">8</text>
+</g>
+<g id="key-num-9">
+  <rect x="104" y="55" width="45" height="40" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="126.5" y="80.0" font-size="14" text-anchor="middle">9</text>
+</g>
+<g id="key-num-plus">
+  <rect x="151" y="55" width="45" height="85" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="173.5" y="102.5" font-size="14" text-anchor="middle">+</text>
+</g>
+<g id="key-num-4">
+  <rect x="10" y="100" width="45" height="40" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="32.5" y="125.0" font-size="14" text-anchor="middle">4</text>
+</g>
+<g id="key-num-5">
+  <rect x="57" y="100" width="45" height="40" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="79.5" y="125.0" font-size="14" text-anchor="middle">5</text>
+</g>
+<g id="key-num-6">
+  <rect x="104" y="100" width="45" height="40" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="126.5" y="125.0" font-size="14" text-anchor="middle">6</text>
+</g>
+<g id="key-num-1">
+  <rect x="10" y="145" width="45" height="40" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="32.5" y="170.0" font-size="14" text-anchor="middle">1</text>
+</g>
+<g id="key-num-2">
+  <rect x="57" y="145" width="45" height="40" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="79.5" y="170.0" font-size="14" text-anchor="middle">2</text>
+</g>
+<g id="key-num-3">
+  <rect x="104" y="145" width="45" height="40" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="126.5" y="170.0" font-size="14" text-anchor="middle">3</text>
+</g>
+<g id="key-num-enter">
+  <rect x="151" y="145" width="45" height="85" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="173.5" y="192.5" font-size="12" text-anchor="middle">Enter</text>
+</g>
+<g id="key-num-0">
+  <rect x="10" y="190" width="92" height="40" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="56.0" y="215.0" font-size="14" text-anchor="middle">0</text>
+</g>
+<g id="key-num-decimal">
+  <rect x="104" y="190" width="45" height="40" rx="6" fill="url(#numGrad)" stroke="black" />
+  <text x="126.5" y="215.0" font-size="14" text-anchor="middle">.</text>
+</g>
+</svg>
+"###;
@@ -0,0 +1,139 @@
+use crate::ascii_chars::AsciiChars;
+use crate::char_names::{spoken_name, symbol_name};
+use crate::common::glyph;
+use crate::layout::NUMPAD_CHARS;
+use serde::de::{self, IntoDeserializer, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// A character the user can be asked to type.
+///
+/// Any Unicode scalar is allowed apart from control characters, with Tab and
+/// Enter (`'\n'`) as the exceptions. How it is typed (a single key, AltGr or a
+/// dead-key sequence) is up to the layout; stats are kept per character.
+/// Numeric keypad keys are kept apart from the main keys that type the same
+/// character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct TargetChar {
+    c: char,
+    numpad: bool,
+}
+
+// Prefix for numpad keys when serialized, e.g. "num:7".
+const NUMPAD_PREFIX: &str = "num:";
+
+impl TargetChar {
+    pub fn new(c: char) -> Option<Self> {
+        match c {
+            '\t' | '\n' | ' ' => Some(TargetChar { c, numpad: false }),
+            _ if c.is_control() || c.is_whitespace() => None,
+            _ => Some(TargetChar { c, numpad: false }),
+        }
+    }
+
+    pub fn numpad(c: char) -> Option<Self> {
+        NUMPAD_CHARS
+            .contains(c)
+            .then_some(TargetChar { c, numpad: true })
+    }
+
+    pub fn as_char(&self) -> char {
+        self.c
+    }
+
+    pub fn is_numpad(&self) -> bool {
+        self.numpad
+    }
+
+    /// The character as shown in the prompt and reports, with a visible
+    /// symbol standing in for whitespace.
+    pub fn as_glyph(&self) -> char {
+        glyph(self.c)
+    }
+
+    pub fn label(&self) -> String {
+        if self.numpad {
+            format!("Num {}", self.as_glyph())
+        } else {
+            self.as_glyph().to_string()
+        }
+    }
+
+    /// What programmers call the character ("left brace"), for those whose
+    /// glyph isn't already their name.
+    pub fn name(&self) -> Option<String> {
+        let name = symbol_name(self.c)?;
+        if self.numpad {
+            Some(format!("keypad {}", name))
+        } else {
+            Some(name.to_string())
+        }
+    }
+
+    /// The character as a screen reader should announce it.
+    pub fn spoken_name(&self) -> String {
+        if self.numpad {
+            format!("keypad {}", spoken_name(self.c))
+        } else {
+            spoken_name(self.c)
+        }
+    }
+}
+
+impl From<AsciiChars> for TargetChar {
+    fn from(key_char: AsciiChars) -> Self {
+        TargetChar {
+            c: key_char.as_char(),
+            numpad: false,
+        }
+    }
+}
+
+impl Serialize for TargetChar {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Written as the character itself (prefixed for numpad keys) so it
+        // also works as a JSON map key.
+        if self.numpad {
+            serializer.serialize_str(&format!("{}{}", NUMPAD_PREFIX, self.c))
+        } else {
+            let mut buf = [0u8; 4];
+            serializer.serialize_str(self.c.encode_utf8(&mut buf))
+        }
+    }
+}
+
+struct TargetCharVisitor;
+
+impl<'de> Visitor<'de> for TargetCharVisitor {
+    type Value = TargetChar;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a single character")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        if let Some(key) = v.strip_prefix(NUMPAD_PREFIX) {
+            let mut chars = key.chars();
+            if let (Some(c), None) = (chars.next(), chars.next()) {
+                return TargetChar::numpad(c)
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Char(c), &self));
+            }
+        }
+        let mut chars = v.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return TargetChar::new(c)
+                .ok_or_else(|| E::invalid_value(de::Unexpected::Char(c), &self));
+        }
+        // Stats saved before this type existed are keyed by `AsciiChars`
+        // variant names ("Exclamation", "Tab", ...).
+        AsciiChars::deserialize(v.into_deserializer())
+            .map(TargetChar::from)
+            .map_err(|_: E| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+impl<'de> Deserialize<'de> for TargetChar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(TargetCharVisitor)
+    }
+}
@@ -0,0 +1,45 @@
+// Builders the unit tests share, so each module's tests can stick to what
+// they're testing.
+use crate::history::HistoryEvent;
+use crate::stats::{HitType, Stats};
+use crate::target_char::TargetChar;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+/// `ms` milliseconds after a fixed moment in early 2025.
+pub fn at(ms: i64) -> NaiveDateTime {
+    DateTime::from_timestamp_millis(1_740_000_000_000 + ms)
+        .unwrap()
+        .naive_utc()
+}
+
+pub fn day(date: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+}
+
+pub fn hit(c: char, ms: i64, reaction_time: u64) -> HistoryEvent {
+    HistoryEvent {
+        char_id: TargetChar::new(c).unwrap(),
+        result: HitType::Hit(at(ms), reaction_time),
+    }
+}
+
+pub fn miss(c: char, ms: i64) -> HistoryEvent {
+    HistoryEvent {
+        char_id: TargetChar::new(c).unwrap(),
+        result: HitType::Miss(at(ms)),
+    }
+}
+
+/// A run's stats, made of `events` in order.
+pub fn stats_of(events: &[HistoryEvent]) -> Stats {
+    let mut stats = Stats::new();
+    for event in events {
+        match event.result {
+            HitType::Hit(timestamp, reaction_time) => {
+                stats.add_hit(event.char_id, timestamp, reaction_time)
+            }
+            HitType::Miss(timestamp) => stats.add_miss(event.char_id, timestamp),
+        }
+    }
+    stats
+}